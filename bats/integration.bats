@@ -45,6 +45,18 @@
   [[ "$output" =~ "Signed-off-by: James Holden <jholden@rocinante.com>" ]]
 }
 
+@test "rotation strategy" {
+  git config --local git-together.rotation driver-only
+  git-together with jh nn ca
+
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  run git config git-together.active
+  [ "$output" = "ca+nn+jh" ]
+}
+
 @test "mobbing" {
   git-together with jh nn ca
 
@@ -82,6 +94,35 @@
   [[ "$output" =~ "Signed-off-by: James Holden <jholden@rocinante.com>" ]]
 }
 
+@test "co-authors" {
+  git config --local git-together.attribution coauthors
+  git-together with jh nn ca
+
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+  run git show --no-patch --format="%cN <%cE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+  run git show --no-patch --format=%B
+  [[ "$output" =~ "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+  [[ "$output" =~ "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>" ]]
+  [[ ! "$output" =~ "Signed-off-by:" ]]
+
+  git-together revert --no-edit HEAD
+
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Naomi Nagata <nnagata@rocinante.com>" ]
+  run git show --no-patch --format="%cN <%cE>"
+  [ "$output" = "Naomi Nagata <nnagata@rocinante.com>" ]
+  run git show --no-patch --format=%B
+  [[ "$output" =~ 'Revert "add foo"' ]]
+  [[ "$output" =~ "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>" ]]
+  [[ "$output" =~ "Co-authored-by: James Holden <jholden@rocinante.com>" ]]
+}
+
 @test "auto-including .git-together" {
   git-together with jh
   run git config --local include.path
@@ -98,6 +139,19 @@
   [ "$output" = "../.git-together" ]
 }
 
+@test "pass-through leaves config alone" {
+  touch .git-together
+  git-together count-objects
+  run git config --local include.path
+  [ "$status" -eq 1 ]
+
+  HOME="$PWD" git-together config --global foo.bar baz
+  run git config --file .gitconfig foo.bar
+  [ "$output" = "baz" ]
+  run git config --local foo.bar
+  [ "$status" -eq 1 ]
+}
+
 @test "list current authors" {
   git-together with jh nn
 
@@ -165,6 +219,23 @@ AUTHORS
   [[ ! "$output" =~ "Signed-off-by:" ]]
 }
 
+@test "merging with co-authors" {
+  git config --local git-together.attribution coauthors
+  git-together with jh nn
+  git commit --allow-empty -m "initial commit"
+  git checkout -b bar
+  git commit --allow-empty -m "add bar"
+  git checkout -
+
+  run git-together merge --no-edit --no-ff bar
+  [ "$status" -eq 0 ]
+  [[ "$output" =~ "warning: \`git merge\` doesn't take trailers" ]]
+  [[ "$output" =~ "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+
+  run git-together commit --allow-empty -m "add baz"
+  [[ ! "$output" =~ "warning:" ]]
+}
+
 @test "reverting" {
   git-together with jh nn
   touch foo
@@ -195,6 +266,492 @@ AUTHORS
   [ "$output" = "" ]
 }
 
+@test "joining and leaving" {
+  git config --add git-together.authors.ak "Alex Kamal; akamal"
+  git-together with jh nn ca
+  git-together with +ak -nn
+  run git config git-together.active
+  [ "$output" = "jh+ca+ak" ]
+
+  git-together with --leave jh --join nn
+  run git config git-together.active
+  [ "$output" = "ca+ak+nn" ]
+
+  run git-together with -jh
+  [ "$status" -ne 0 ]
+}
+
+@test "groups" {
+  git config --add git-together.groups.crew "jh+nn"
+  git-together with @crew ca
+  run git config git-together.active
+  [ "$output" = "jh+nn+ca" ]
+
+  run git-together with --list
+  [ "${lines[3]}" = "@crew: jh+nn" ]
+
+  git config --add git-together.groups.marines "bd"
+  run git-together with @marines
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "invalid member 'bd' in group '@marines'" ]]
+}
+
+@test "history" {
+  git-together with jh nn
+  git-together with ca
+  git-together with -
+  run git config git-together.active
+  [ "$output" = "jh+nn" ]
+
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+  git-together with -
+  run git config git-together.active
+  [ "$output" = "ca" ]
+
+  run git-together with --history
+  [ "${#lines[@]}" -eq 5 ]
+  [[ "${lines[0]}" =~ "manual    nn+jh -> ca" ]]
+  [[ "${lines[1]}" =~ "rotation  jh+nn -> nn+jh" ]]
+  [[ "${lines[4]}" =~ "manual    (nobody) -> jh+nn" ]]
+}
+
+@test "pausing" {
+  git-together with jh nn
+  git-together with --pause
+  run git-together with
+  [ "${lines[0]}" = "paused (git with --resume to continue)" ]
+
+  touch foo
+  git add foo
+  git-together -c user.name="Bobbie Draper" -c user.email=bdraper@mars.mil commit -m "add foo"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Bobbie Draper <bdraper@mars.mil>" ]
+  run git config git-together.active
+  [ "$output" = "jh+nn" ]
+
+  git-together with --resume
+  touch bar
+  git add bar
+  git-together commit -m "add bar"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+}
+
+@test "policy" {
+  git config user.name "Bobbie Draper"
+  git config user.email bdraper@mars.mil
+  touch foo
+  git add foo
+
+  run git-together commit -m "add foo"
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "no active pair" ]]
+
+  git config git-together.policy warn
+  run git-together commit -m "add foo"
+  [ "$status" -eq 0 ]
+  [[ "$output" =~ "warning: no active pair" ]]
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Bobbie Draper <bdraper@mars.mil>" ]
+}
+
+@test "session expiry" {
+  git-together with jh nn
+  git config git-together.started $(( $(date +%s) - 50000 ))
+  touch foo
+  git add foo
+
+  run git-together commit -m "add foo"
+  [ "$status" -eq 0 ]
+  [[ "$output" =~ "warning: the pair was set 13h ago" ]]
+
+  git config git-together.expireAfter 12h
+  touch bar
+  git add bar
+  run git-together commit -m "add bar"
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "has expired" ]]
+
+  git-together with jh nn
+  git-together commit -m "add bar"
+}
+
+@test "per-branch pairs" {
+  git-together with jh nn
+  git config git-together.perBranch true
+  git checkout -b feature
+  git-together with ca jh
+
+  run git-together with
+  [ "${lines[0]}" = "pair for branch 'feature'" ]
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Chrisjen Avasarala <avasarala@un.gov>" ]
+
+  git checkout -b other
+  touch bar
+  git add bar
+  git-together commit -m "add bar"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+  run git config git-together.active
+  [ "$output" = "nn+jh" ]
+}
+
+@test "worktrees" {
+  git commit --allow-empty -m "initial commit"
+  git worktree add ../worktrees-other
+  git-together with --list
+  run git config extensions.worktreeConfig
+  [ "$status" -eq 1 ]
+
+  git-together with jh nn
+  run git config extensions.worktreeConfig
+  [ "$output" = "true" ]
+
+  cd ../worktrees-other
+  git-together with ca
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Chrisjen Avasarala <avasarala@un.gov>" ]
+
+  cd -
+  run git config git-together.active
+  [ "$output" = "jh+nn" ]
+  run git config --worktree git-together.active
+  [ "$output" = "jh+nn" ]
+  run git config --local git-together.active
+  [ "$status" -eq 1 ]
+}
+
+@test "worktrees added after pairing" {
+  git commit --allow-empty -m "initial commit"
+  git-together with jh nn
+  git worktree add ../worktrees-later
+
+  git-together with --pause
+  run git config --local git-together.paused
+  [ "$output" = "true" ]
+  run git config extensions.worktreeConfig
+  [ "$status" -eq 1 ]
+  run git-together with
+  [ "${lines[0]}" = "paused (git with --resume to continue)" ]
+
+  git-together with --resume
+  git-together with --clear
+  run git config git-together.active
+  [ "$status" -eq 1 ]
+}
+
+@test "pausing a global pair" {
+  export HOME="$PWD"
+  git config --global git-together.domain rocinante.com
+  git config --global git-together.authors.jh "James Holden; jholden"
+  git config --global git-together.authors.nn "Naomi Nagata; nnagata"
+  git-together with --global jh nn
+  git-together with --pause
+  run git config --global git-together.paused
+  [ "$output" = "true" ]
+  run git config --local git-together.paused
+  [ "$status" -eq 1 ]
+
+  run git-together with --local --pause
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "use --global" ]]
+
+  git-together with --resume
+  git-together with --clear
+}
+
+@test "managing authors" {
+  git-together with --file --add ak "Alex Kamal; akamal"
+  run git config --file .git-together git-together.authors.ak
+  [ "$output" = "Alex Kamal; akamal" ]
+  run git config --local include.path
+  [ "$output" = "../.git-together" ]
+
+  run git-together with --file --add ak "Amos Burton; aburton"
+  [ "$status" -ne 0 ]
+
+  run git-together with --add am "Amos Burton"
+  [ "$status" -ne 0 ]
+
+  run git-together with --rename ak am
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "use --file" ]]
+  run git config --local git-together.authors.am
+  [ "$status" -eq 1 ]
+
+  git-together with --file --rename ak am
+  run git config --file .git-together git-together.authors.am
+  [ "$output" = "Alex Kamal; akamal" ]
+
+  git-together with --file --edit am "Amos Burton; aburton"
+  run git config --file .git-together git-together.authors.am
+  [ "$output" = "Amos Burton; aburton" ]
+
+  run git-together with --remove am
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "use --file" ]]
+
+  run git-together with --file jh nn
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "--file only works with" ]]
+  run git config --file .git-together git-together.active
+  [ "$status" -eq 1 ]
+
+  git-together with --file --remove am
+  run git config --file .git-together git-together.authors.am
+  [ "$status" -eq 1 ]
+
+  git-together with --file --add ak "Alex Kamal; akamal"
+  run git-together with --add ak "Amos Burton; aburton" --force
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "use --file" ]]
+  git-together with --file --remove ak
+
+  run git-together with --add am "Amos Burton <aburton>"
+  [ "$output" = "am: Amos Burton <aburton@rocinante.com>" ]
+  run git-together with --add jm '"Miller; Joe" <jmiller@starhelix.com>'
+  [ "$output" = "jm: Miller; Joe <jmiller@starhelix.com>" ]
+}
+
+@test "structured authors" {
+  git config --add git-together.author.ak.name "Alex Kamal"
+  git config --add git-together.author.ak.email akamal
+  git config --add git-together.author.ak.github akamal
+  git-together with ak
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Alex Kamal <akamal@rocinante.com>" ]
+
+  git-together with --edit ak "Alex Kamal; alex"
+  run git config git-together.author.ak.email
+  [ "$output" = "alex@rocinante.com" ]
+  run git config git-together.author.ak.github
+  [ "$output" = "akamal" ]
+
+  git config --add git-together.author.ak.active false
+  run git-together together suggest
+  [[ ! "$output" =~ "ak" ]]
+}
+
+@test "invalid authors" {
+  git config --file .git-together --add git-together.authors.ak "Alex Kamal; akamal@@rocinante.com"
+  git config --add include.path ../.git-together
+  run git-together with ak
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "invalid git-together.authors.ak in $PWD/.git-together" ]]
+  [[ "$output" =~ "more than one '@'" ]]
+
+  git config git-together.domain "rocinante.com "
+  run git-together with jh
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "invalid git-together.domain in $PWD/.git/config" ]]
+}
+
+@test "stats" {
+  git-together with jh nn
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  git-together with ca
+  touch bar
+  git add bar
+  git-together commit -m "add bar"
+
+  run git-together together stats
+  expected=$(cat <<STATS
+Pairs:
+  ca: 1
+  jh+nn: 1
+People:
+  ca: 1
+  jh: 1
+  nn: 1
+STATS
+)
+  [ "$output" = "$expected" ]
+
+  run git-together together stats HEAD~1
+  [[ ! "$output" =~ "ca" ]]
+}
+
+@test "stairs" {
+  git-together with jh nn
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  git-together with ca
+  touch bar
+  git add bar
+  git-together commit -m "add bar"
+
+  run git-together together stairs --format csv
+  expected=$(cat <<STAIRS
+,ca,jh,nn
+ca,1,,
+jh,0,0,
+nn,0,1,0
+STAIRS
+)
+  [ "$output" = "$expected" ]
+}
+
+@test "suggest" {
+  git-together with jh nn
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  run git-together together suggest jh nn ca
+  expected=$(cat <<PAIRS
+jh
+nn+ca
+PAIRS
+)
+  [ "$output" = "$expected" ]
+
+  git config --local git-together.user.email avasarala@un.gov
+  git-together together suggest --set jh nn ca
+  run git config git-together.active
+  [ "$output" = "ca+nn" ]
+}
+
+@test "hooks" {
+  git-together with jh nn ca
+  git-together together install-hooks
+
+  touch foo
+  git add foo
+  git commit -m "add foo"
+
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+  run git show --no-patch --format=%B
+  [[ "$output" =~ "Signed-off-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+  run git config git-together.active
+  [ "$output" = "nn+ca+jh" ]
+
+  touch bar
+  git add bar
+  git-together commit -m "add bar"
+
+  run git show --no-patch --format=%B
+  [ "$(grep -c "Signed-off-by:" <<< "$output")" -eq 1 ]
+  run git config git-together.active
+  [ "$output" = "ca+jh+nn" ]
+
+  echo "#!/bin/sh" > .git/hooks/commit-msg
+  run git-together together install-hooks
+  [ "$status" -ne 0 ]
+}
+
+@test "amending with hooks after rotating" {
+  git config git-together.attribution coauthors
+  git-together with jh nn ca
+  git-together together install-hooks
+  touch foo
+  git add foo
+  git commit -m "add foo"
+  run git config git-together.active
+  [ "$output" = "nn+ca+jh" ]
+
+  git commit --amend --no-edit
+  run git show --no-patch --format="%aN <%aE>"
+  [ "$output" = "James Holden <jholden@rocinante.com>" ]
+  run git show --no-patch --format=%B
+  [[ ! "$output" =~ "Co-authored-by: James Holden" ]]
+  [[ "$output" =~ "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+  [[ "$output" =~ "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>" ]]
+}
+
+@test "session trailers" {
+  git-together with jh nn --trailer "Story: ABC-123" --trailer "Ticket: 42"
+  touch foo
+  git add foo
+  git-together commit -m "add foo"
+
+  run git show --no-patch --format=%B
+  [[ "$output" =~ "Story: ABC-123" ]]
+  [[ "$output" =~ "Ticket: 42" ]]
+
+  git-together with nn --keep-trailers
+  run git config --get-all git-together.trailer
+  [ "${#lines[@]}" -eq 2 ]
+
+  git-together with --clear
+  run git config --get-all git-together.trailer
+  [ "$status" -eq 1 ]
+}
+
+@test "output formats" {
+  run git-together with --format json jh nn
+  [ "$output" = '{"active":[{"initials":"jh","name":"James Holden","email":"jholden@rocinante.com","scope":"local"},{"initials":"nn","name":"Naomi Nagata","email":"nnagata@rocinante.com","scope":"local"}],"scope":"local","branch":null,"paused":false,"trailers":[]}' ]
+
+  run git-together with --format json
+  [ "$output" = '{"active":[{"initials":"jh","name":"James Holden","email":"jholden@rocinante.com","scope":"local"},{"initials":"nn","name":"Naomi Nagata","email":"nnagata@rocinante.com","scope":"local"}],"scope":"local","branch":null,"paused":false,"trailers":[]}' ]
+
+  run git-together with --format "%i %n <%e> (%s)"
+  expected=$(cat <<AUTHORS
+jh James Holden <jholden@rocinante.com> (local)
+nn Naomi Nagata <nnagata@rocinante.com> (local)
+AUTHORS
+)
+  [ "$output" = "$expected" ]
+
+  run git-together with --list --format json
+  [ "$output" = '{"authors":[{"initials":"ca","name":"Chrisjen Avasarala","email":"avasarala@un.gov","scope":"local"},{"initials":"jh","name":"James Holden","email":"jholden@rocinante.com","scope":"local"},{"initials":"nn","name":"Naomi Nagata","email":"nnagata@rocinante.com","scope":"local"}],"groups":[]}' ]
+
+  git-together with --file --add ak "Alex Kamal; akamal"
+  run git-together with --list --format "%i %s"
+  [ "${lines[0]}" = "ak file" ]
+
+  run git-together with --format jsno
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "invalid format: 'jsno'" ]]
+}
+
+@test "installed as git" {
+  mkdir bin
+  cp "$(command -v git-together)" bin/git
+  PATH="$PWD/bin:$PATH" git with jh
+  run git config git-together.active
+  [ "$output" = "jh" ]
+
+  GIT_TOGETHER_GIT="$PWD/bin/git" run bin/git status
+  [ "$status" -ne 0 ]
+  [[ "$output" =~ "git-together called itself" ]]
+}
+
+@test "hooks installed as git" {
+  real_git="$(command -v git)"
+  mkdir bin
+  cp "$(command -v git-together)" bin/git
+  export PATH="$PWD/bin:$(dirname "$real_git"):/usr/bin:/bin"
+  run command -v git-together
+  [ "$status" -ne 0 ]
+
+  git with jh nn
+  git together install-hooks
+  touch foo
+  "$real_git" add foo
+  "$real_git" commit -m "add foo"
+
+  run "$real_git" show --no-patch --format=%B
+  [[ "$output" =~ "Signed-off-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+}
+
 @test "together" {
   git-together together jh nn
   touch foo
@@ -223,6 +780,16 @@ AUTHORS
   [ "$output" = "Naomi Nagata <nnagata@rocinante.com>" ]
   run git show --no-patch --format=%B
   [[ "$output" =~ "Signed-off-by: Naomi Nagata <nnagata@rocinante.com>" ]]
+
+  git config --local alias.rv revert
+  git config --local git-together.aliases m,ci,r,rv
+  git-together with jh nn --trailer "Story: ABC-1"
+  git-together rv --no-edit HEAD
+
+  run git show --no-patch --format=%s
+  [ "$output" = 'Revert "add foo"' ]
+  run git show --no-patch --format=%B
+  [[ "$output" =~ "Story: ABC-1" ]]
 }
 
 @test "global args" {
@@ -232,6 +799,25 @@ AUTHORS
   git-together -c commit.verbose=false commit -m "add foo"
 }
 
+@test "other repos" {
+  git init other
+  git -C other config --add git-together.domain rocinante.com
+  git -C other config --add git-together.authors.ak "Alex Kamal; akamal"
+
+  git-together -C other with ak
+  run git config git-together.active
+  [ "$status" -eq 1 ]
+
+  touch other/foo
+  git-together -C other add foo
+  git-together -C other commit -m "add foo"
+  run git -C other show --no-patch --format="%aN <%aE>"
+  [ "$output" = "Alex Kamal <akamal@rocinante.com>" ]
+
+  run git-together --git-dir=other/.git --work-tree=other with
+  [ "$output" = "ak: Alex Kamal <akamal@rocinante.com>" ]
+}
+
 setup() {
   # [ -f $BATS_TMPDIR/bin/git-together ] || cargo install --root $BATS_TMPDIR
   rm -rf $BATS_TMPDIR/bin
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
 use crate::errors::*;
@@ -6,6 +7,23 @@ use crate::errors::*;
 pub struct Author {
     pub name: String,
     pub email: String,
+    /// Anything else set for an author in the structured form, keyed by the
+    /// lowercase field name (`github`, `signingkey`, ...)
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Author {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    /// Whether the author is still around, which is only false when
+    /// `active` is turned off in the structured form.
+    pub fn is_active(&self) -> bool {
+        self.field("active").is_none_or(|active| {
+            !matches!(active.to_lowercase().as_str(), "false" | "no" | "off" | "0")
+        })
+    }
 }
 
 pub struct AuthorParser {
@@ -13,40 +31,191 @@ pub struct AuthorParser {
 }
 
 impl AuthorParser {
+    /// Parses either `Name; email` or `Name <email>`, where the email can
+    /// be an email seed that gets the default domain. Names with a `;` or
+    /// `<` in them can be put in double quotes.
     pub fn parse(&self, raw: &str) -> Result<Author> {
-        let mut split = raw.split(';').map(str::trim);
+        let (name, rest) = split_name(raw.trim())?;
+        if name.is_empty() {
+            return Err("missing name".into());
+        }
+
+        let email_seed = if let Some(rest) = rest.strip_prefix('<') {
+            let email_seed = rest
+                .strip_suffix('>')
+                .ok_or("missing '>' after the email")?;
+            if email_seed.contains(['<', '>']) {
+                return Err(format!("invalid email: '{}'", email_seed).into());
+            }
+            email_seed
+        } else if let Some(rest) = rest.strip_prefix(';') {
+            rest.split(';').next().unwrap_or("")
+        } else {
+            rest
+        };
+
+        let email_seed = email_seed.trim();
+        if email_seed.is_empty() {
+            return Err("missing email seed".into());
+        }
+
+        validate_name(&name)?;
+        Ok(Author {
+            name,
+            email: self.email(email_seed)?,
+            fields: BTreeMap::new(),
+        })
+    }
 
-        let name = match split.next() {
-            Some(name) if !name.is_empty() => name,
+    /// Builds an author from the fields of a `[git-together "author.<initials>"]`
+    /// section, where `email` can be an email seed just like in the string
+    /// form.
+    pub fn parse_fields(&self, mut fields: BTreeMap<String, String>) -> Result<Author> {
+        let name = match fields.remove("name") {
+            Some(name) if !name.trim().is_empty() => name.trim().to_string(),
             _ => {
                 return Err("missing name".into());
             }
         };
 
-        let email_seed = match split.next() {
-            Some(email_seed) if !email_seed.is_empty() => email_seed,
+        validate_name(&name)?;
+
+        let email = match fields.remove("email") {
+            Some(email_seed) if !email_seed.trim().is_empty() => self.email(email_seed.trim())?,
             _ => {
-                return Err("missing email seed".into());
+                return Err("missing email".into());
             }
         };
 
+        Ok(Author {
+            name,
+            email,
+            fields,
+        })
+    }
+
+    fn email(&self, email_seed: &str) -> Result<String> {
         let email = if email_seed.contains('@') {
             email_seed.into()
         } else {
-            let domain = match self.domain {
-                Some(ref domain) => domain,
-                None => {
-                    return Err("missing domain".into());
-                }
-            };
-            format!("{}@{}", email_seed, domain)
+            match self.domain {
+                Some(ref domain) => format!("{}@{}", email_seed, domain),
+                None => return Err("missing domain".into()),
+            }
         };
 
-        Ok(Author {
-            name: name.into(),
-            email,
-        })
+        validate_email(&email)?;
+        Ok(email)
+    }
+}
+
+/// Names end up in commit headers and trailers, where a newline would start
+/// a new trailer.
+fn validate_name(name: &str) -> Result<()> {
+    if name.contains(char::is_control) {
+        return Err(format!(
+            "invalid name '{}': contains control characters",
+            name.escape_debug()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Checks an email address against the dot-atom form from RFC 5322, which
+/// is what code hosts match commits on.
+pub fn validate_email(email: &str) -> Result<()> {
+    let invalid = |reason: &str| format!("invalid email '{}': {}", email.escape_debug(), reason);
+
+    if email.contains(|c: char| c.is_control() || c.is_whitespace()) {
+        return Err(invalid("contains whitespace or control characters").into());
+    }
+    let (local, domain) = match email.split_once('@') {
+        Some((_, domain)) if domain.contains('@') => {
+            return Err(invalid("more than one '@'").into());
+        }
+        Some(parts) => parts,
+        None => return Err(invalid("missing '@'").into()),
+    };
+
+    const ATEXT: &str = "!#$%&'*+-/=?^_`{|}~";
+    if local.is_empty() {
+        return Err(invalid("nothing before the '@'").into());
+    }
+    if local.len() > 64 {
+        return Err(invalid("more than 64 characters before the '@'").into());
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err(invalid("misplaced '.' before the '@'").into());
+    }
+    if let Some(c) = local
+        .chars()
+        .find(|&c| !(c.is_alphanumeric() || c == '.' || ATEXT.contains(c)))
+    {
+        return Err(invalid(&format!("'{}' isn't allowed before the '@'", c)).into());
+    }
+
+    validate_domain(domain).chain_err(|| invalid("invalid domain"))
+}
+
+/// Checks a domain name: dot-separated labels of letters, digits and
+/// hyphens.
+pub fn validate_domain(domain: &str) -> Result<()> {
+    let invalid = |reason: &str| format!("invalid domain '{}': {}", domain.escape_debug(), reason);
+
+    if domain.is_empty() {
+        return Err(invalid("empty").into());
+    }
+    if domain.len() > 253 {
+        return Err(invalid("more than 253 characters").into());
+    }
+    for label in domain.split('.') {
+        if label.is_empty() {
+            return Err(invalid("misplaced '.'").into());
+        }
+        if label.len() > 63 {
+            return Err(invalid(&format!("'{}' is more than 63 characters", label)).into());
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(invalid(&format!("'{}' starts or ends with '-'", label)).into());
+        }
+        if let Some(c) = label.chars().find(|&c| !(c.is_alphanumeric() || c == '-')) {
+            return Err(invalid(&format!("'{}' isn't allowed", c.escape_debug())).into());
+        }
     }
+    Ok(())
+}
+
+/// Splits off the name, which ends at the first `;` or `<` unless it's
+/// quoted, returning it along with the rest.
+fn split_name(raw: &str) -> Result<(String, &str)> {
+    let quoted = match raw.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let end = raw.find([';', '<']).unwrap_or(raw.len());
+            return Ok((raw[..end].trim().into(), &raw[end..]));
+        }
+    };
+
+    let mut name = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let rest = quoted[i + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with([';', '<']) {
+                    return Err(format!("unexpected '{}' after the quoted name", rest).into());
+                }
+                return Ok((name.trim().into(), rest));
+            }
+            '\\' => match chars.next() {
+                Some((_, escaped)) => name.push(escaped),
+                None => break,
+            },
+            c => name.push(c),
+        }
+    }
+    Err("missing closing '\"' after the name".into())
 }
 
 impl fmt::Display for Author {
@@ -84,4 +253,177 @@ mod tests {
         assert_eq!(author.name, "Jane Doe");
         assert_eq!(author.email, "jane.doe@example.edu");
     }
+
+    #[test]
+    fn angle_brackets() {
+        let author_parser = AuthorParser {
+            domain: Some("example.com".into()),
+        };
+
+        let author = author_parser
+            .parse("Jane Doe <jane.doe@example.edu>")
+            .unwrap();
+        assert_eq!(author.name, "Jane Doe");
+        assert_eq!(author.email, "jane.doe@example.edu");
+
+        let author = author_parser.parse("  Jane Doe<jdoe>  ").unwrap();
+        assert_eq!(author.name, "Jane Doe");
+        assert_eq!(author.email, "jdoe@example.com");
+
+        let author = author_parser.parse("Jane Doe < jdoe >").unwrap();
+        assert_eq!(author.email, "jdoe@example.com");
+
+        assert!(author_parser.parse("<jdoe>").is_err());
+        assert!(author_parser.parse("Jane Doe <>").is_err());
+        assert!(author_parser.parse("Jane Doe <jdoe").is_err());
+        assert!(author_parser.parse("Jane Doe <jdoe> extra").is_err());
+        assert!(author_parser.parse("Jane Doe <j<doe>").is_err());
+        assert!(author_parser.parse("Jane Doe <jdoe>>").is_err());
+
+        let author_parser = AuthorParser { domain: None };
+        assert!(author_parser.parse("Jane Doe <jdoe>").is_err());
+        assert!(author_parser.parse("Jane Doe <jdoe@example.com>").is_ok());
+    }
+
+    #[test]
+    fn quoted_names() {
+        let author_parser = AuthorParser {
+            domain: Some("example.com".into()),
+        };
+
+        let author = author_parser.parse(r#""Doe; Jane" <jdoe>"#).unwrap();
+        assert_eq!(author.name, "Doe; Jane");
+        assert_eq!(author.email, "jdoe@example.com");
+
+        let author = author_parser.parse(r#""Jane <JD> Doe"; jdoe"#).unwrap();
+        assert_eq!(author.name, "Jane <JD> Doe");
+        assert_eq!(author.email, "jdoe@example.com");
+
+        let author = author_parser
+            .parse(r#""Jane \"JD\" Doe \\o/"<jdoe>"#)
+            .unwrap();
+        assert_eq!(author.name, r#"Jane "JD" Doe \o/"#);
+
+        assert!(author_parser.parse(r#""" <jdoe>"#).is_err());
+        assert!(author_parser.parse(r#""Jane Doe <jdoe>"#).is_err());
+        assert!(author_parser.parse(r#""Jane" Doe <jdoe>"#).is_err());
+        assert!(author_parser.parse(r#""Jane Doe""#).is_err());
+    }
+
+    #[test]
+    fn emails() {
+        for email in [
+            "jdoe@example.com",
+            "jane.doe+git@mail.example.co.uk",
+            "o'neil@example.com",
+            "jdoe@localhost",
+            "jdoe@xn--bcher-kva.example",
+            "jdoe@bücher.example",
+        ] {
+            assert!(validate_email(email).is_ok(), "{}", email);
+        }
+
+        for email in [
+            "jdoe",
+            "jdoe@@example.com",
+            "jdoe@exa@mple.com",
+            "@example.com",
+            "jdoe@",
+            "jdoe@example..com",
+            "jdoe@example.com.",
+            "jdoe@.example.com",
+            "jdoe@-example.com",
+            "jdoe@example_site.com",
+            ".jdoe@example.com",
+            "jane..doe@example.com",
+            "jane doe@example.com",
+            "jdoe@example.com ",
+            "jdoe@example.com\nSigned-off-by: Mallory <m@example.com>",
+            "j(doe)@example.com",
+            "jdoe@[127.0.0.1]",
+        ] {
+            assert!(validate_email(email).is_err(), "{}", email);
+        }
+
+        assert!(validate_email(&format!("{}@example.com", "a".repeat(65))).is_err());
+        assert!(validate_email(&format!("jdoe@{}.com", "a".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn domains() {
+        assert!(validate_domain("rocinante.com").is_ok());
+        assert!(validate_domain("un.gov").is_ok());
+        assert!(validate_domain("").is_err());
+        assert!(validate_domain("rocinante.com ").is_err());
+        assert!(validate_domain("@rocinante.com").is_err());
+        assert!(validate_domain("rocinante..com").is_err());
+
+        let error = validate_domain("rocinante.com ").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid domain 'rocinante.com ': ' ' isn't allowed"
+        );
+    }
+
+    #[test]
+    fn validation() {
+        let author_parser = AuthorParser {
+            domain: Some("example.com".into()),
+        };
+
+        assert!(author_parser.parse("Jane Doe; jdoe@@example").is_err());
+        assert!(author_parser.parse("Jane Doe; j doe").is_err());
+        assert!(author_parser
+            .parse("\"Jane Doe\nCo-authored-by: Mallory\" <jdoe>")
+            .is_err());
+        assert!(author_parser.parse("Jane\tDoe <jdoe>").is_err());
+
+        let author_parser = AuthorParser {
+            domain: Some("example..com".into()),
+        };
+        assert!(author_parser.parse("Jane Doe; jdoe").is_err());
+        assert!(author_parser.parse("Jane Doe; jdoe@example.com").is_ok());
+    }
+
+    #[test]
+    fn parse_fields() {
+        let author_parser = AuthorParser {
+            domain: Some("example.com".into()),
+        };
+        let fields = |fields: &[(&str, &str)]| -> BTreeMap<String, String> {
+            fields.iter().map(|&(k, v)| (k.into(), v.into())).collect()
+        };
+
+        let author = author_parser
+            .parse_fields(fields(&[
+                ("name", "Jane Doe"),
+                ("email", "jdoe"),
+                ("github", "janedoe"),
+                ("active", "false"),
+            ]))
+            .unwrap();
+        assert_eq!(author.name, "Jane Doe");
+        assert_eq!(author.email, "jdoe@example.com");
+        assert_eq!(author.field("github"), Some("janedoe"));
+        assert_eq!(author.field("signingKey"), None);
+        assert!(!author.is_active());
+
+        let author = author_parser
+            .parse_fields(fields(&[
+                ("name", "Jane Doe"),
+                ("email", "jane.doe@example.edu"),
+                ("signingkey", "ABC123"),
+            ]))
+            .unwrap();
+        assert_eq!(author.email, "jane.doe@example.edu");
+        assert_eq!(author.field("signingKey"), Some("ABC123"));
+        assert!(author.is_active());
+
+        assert!(author_parser
+            .parse_fields(fields(&[("name", "Jane Doe")]))
+            .is_err());
+        assert!(author_parser
+            .parse_fields(fields(&[("email", "jdoe")]))
+            .is_err());
+    }
 }
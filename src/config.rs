@@ -3,8 +3,22 @@ use std::collections::HashMap;
 
 pub trait Config {
     fn get(&self, name: &str) -> Result<String>;
+    /// Which config level (`system`, `global`, `local`, ...) `name` comes from
+    fn scope(&self, name: &str) -> Result<String>;
+    /// The file `name` comes from, as best as can be told, for error messages
+    fn origin(&self, name: &str) -> Result<String>;
+    /// Whether `name` is set in the file that changes go to, rather than
+    /// coming from another scope or an included file
+    fn writable(&self, name: &str) -> bool;
+    /// Whether a new value for `name` can be set over the current one, which
+    /// isn't the case when it's included into the file that changes go to
+    fn shadowable(&self, name: &str) -> bool;
     fn get_all(&self, glob: &str) -> Result<HashMap<String, String>>;
+    /// Every value of a multivar, from the highest scope that sets it
+    fn get_multi(&self, name: &str) -> Result<Vec<String>>;
     fn add(&mut self, name: &str, value: &str) -> Result<()>;
     fn set(&mut self, name: &str, value: &str) -> Result<()>;
     fn clear(&mut self, name: &str) -> Result<()>;
+    /// Removes every value of a multivar, if there are any
+    fn clear_all(&mut self, name: &str) -> Result<()>;
 }
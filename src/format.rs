@@ -0,0 +1,206 @@
+use std::fmt;
+
+use crate::author::Author;
+use crate::errors::*;
+
+/// How `git with` prints authors: plain text, JSON, or a template with
+/// `%i` (initials), `%n` (name), `%e` (email) and `%s` (config scope).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format<'a> {
+    Text,
+    Json,
+    Template(&'a str),
+}
+
+impl<'a> Format<'a> {
+    /// Anything other than `text` or `json` is a template, which needs at
+    /// least one `%` so that typos like `jsno` aren't printed as is.
+    pub fn new(raw: Option<&'a str>) -> Result<Self> {
+        match raw {
+            None | Some("text") => Ok(Format::Text),
+            Some("json") => Ok(Format::Json),
+            Some(template) if template.contains('%') => Ok(Format::Template(template)),
+            Some(other) => Err(format!("invalid format: '{}'", other).into()),
+        }
+    }
+}
+
+/// How `git together stairs` prints its table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StairsFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+impl StairsFormat {
+    pub fn new(raw: Option<&str>) -> Result<Self> {
+        match raw {
+            None | Some("text") => Ok(StairsFormat::Text),
+            Some("markdown") => Ok(StairsFormat::Markdown),
+            Some("csv") => Ok(StairsFormat::Csv),
+            Some(other) => Err(format!("invalid format: '{}'", other).into()),
+        }
+    }
+}
+
+pub fn template(template: &str, initials: &str, author: &Author, scope: &str) -> String {
+    let mut output = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('i') => output.push_str(initials),
+            Some('n') => output.push_str(&author.name),
+            Some('e') => output.push_str(&author.email),
+            Some('s') => output.push_str(scope),
+            Some('%') => output.push('%'),
+            Some(other) => {
+                output.push('%');
+                output.push(other);
+            }
+            None => output.push('%'),
+        }
+    }
+    output
+}
+
+pub fn author_json(initials: &str, author: &Author, scope: &str) -> Json {
+    Json::Object(vec![
+        ("initials", Json::String(initials.into())),
+        ("name", Json::String(author.name.clone())),
+        ("email", Json::String(author.email.clone())),
+        ("scope", Json::String(scope.into())),
+    ])
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_json_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(Format::new(None).unwrap(), Format::Text);
+        assert_eq!(Format::new(Some("text")).unwrap(), Format::Text);
+        assert_eq!(Format::new(Some("json")).unwrap(), Format::Json);
+        assert_eq!(Format::new(Some("%i")).unwrap(), Format::Template("%i"));
+        assert!(Format::new(Some("jsno")).is_err());
+        assert!(Format::new(Some("")).is_err());
+        assert!(Format::new(Some("csv")).is_err());
+    }
+
+    #[test]
+    fn stairs_formats() {
+        assert_eq!(StairsFormat::new(None).unwrap(), StairsFormat::Text);
+        assert_eq!(StairsFormat::new(Some("text")).unwrap(), StairsFormat::Text);
+        assert_eq!(
+            StairsFormat::new(Some("markdown")).unwrap(),
+            StairsFormat::Markdown
+        );
+        assert_eq!(StairsFormat::new(Some("csv")).unwrap(), StairsFormat::Csv);
+        assert!(StairsFormat::new(Some("json")).is_err());
+        assert!(StairsFormat::new(Some("%i")).is_err());
+    }
+
+    #[test]
+    fn templates() {
+        let author = Author {
+            name: "James Holden".into(),
+            email: "jholden@rocinante.com".into(),
+            fields: Default::default(),
+        };
+
+        assert_eq!(
+            template("%i %n <%e> (%s)", "jh", &author, "local"),
+            "jh James Holden <jholden@rocinante.com> (local)"
+        );
+        assert_eq!(template("100%% %x %", "jh", &author, "local"), "100% %x %");
+    }
+
+    #[test]
+    fn json() {
+        let author = Author {
+            name: "James \"Jim\" Holden\\".into(),
+            email: "jholden@rocinante.com".into(),
+            fields: Default::default(),
+        };
+
+        assert_eq!(
+            author_json("jh", &author, "global").to_string(),
+            r#"{"initials":"jh","name":"James \"Jim\" Holden\\","email":"jholden@rocinante.com","scope":"global"}"#
+        );
+        assert_eq!(
+            Json::Array(vec![
+                Json::Null,
+                Json::Bool(true),
+                Json::Number(-1),
+                Json::String("a\nb\u{1}".into()),
+            ])
+            .to_string(),
+            r#"[null,true,-1,"a\nb\u0001"]"#
+        );
+        assert_eq!(Json::Object(vec![]).to_string(), "{}");
+    }
+}
@@ -1,27 +1,143 @@
 use std::collections::HashMap;
 use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Once;
 
 use crate::config;
 use crate::errors::*;
+use crate::history;
 use crate::ConfigScope;
 
+/// Finds the real git: `GIT_TOGETHER_GIT`, then the `configured` path, then
+/// the first `git` on `PATH` that isn't this executable, so that
+/// git-together can be installed as `git` itself.
+pub fn binary(configured: Option<String>) -> Result<PathBuf> {
+    find_binary(
+        env::var_os("GIT_TOGETHER_GIT"),
+        configured,
+        &env::var_os("PATH").unwrap_or_default(),
+    )
+}
+
+fn find_binary(
+    overridden: Option<OsString>,
+    configured: Option<String>,
+    path: &OsStr,
+) -> Result<PathBuf> {
+    if let Some(path) = overridden.filter(|path| !path.is_empty()) {
+        return Ok(path.into());
+    }
+    if let Some(path) = configured.filter(|path| !path.is_empty()) {
+        return Ok(path.into());
+    }
+
+    let current_exe = env::current_exe().and_then(fs::canonicalize).ok();
+    env::split_paths(path)
+        .map(|dir| dir.join(format!("git{}", env::consts::EXE_SUFFIX)))
+        .find(|git| git.is_file() && fs::canonicalize(git).ok() != current_exe)
+        .ok_or_else(|| {
+            "couldn't find git on PATH (set GIT_TOGETHER_GIT or git-together.gitPath)".into()
+        })
+}
+
+const WORKTREE_CONFIG: &str = "config.worktree";
+
 pub struct Repo {
     repo: git2::Repository,
 }
 
 impl Repo {
+    /// Finds the repo the same way git does, honoring `GIT_DIR` and
+    /// `GIT_WORK_TREE`.
     pub fn new() -> Result<Self> {
-        let repo = env::current_dir()
-            .chain_err(|| "")
-            .and_then(|current_dir| git2::Repository::discover(current_dir).chain_err(|| ""))?;
+        // libgit2 doesn't know about `extensions.worktreeConfig` and refuses
+        // to open repos using it unless told it's fine
+        static EXTENSIONS: Once = Once::new();
+        EXTENSIONS.call_once(|| {
+            // SAFETY: this runs once, before any repo is opened
+            let _ = unsafe { git2::opts::set_extensions(&["worktreeconfig"]) };
+        });
+
+        let repo = match env::var_os("GIT_DIR") {
+            Some(git_dir) => git2::Repository::open(&git_dir)
+                .chain_err(|| format!("error opening '{}'", Path::new(&git_dir).display()))?,
+            None => env::current_dir()
+                .chain_err(|| "error getting the current directory")
+                .and_then(|current_dir| {
+                    git2::Repository::discover(current_dir).chain_err(|| "not in a git repository")
+                })?,
+        };
+
+        if let Some(work_tree) = env::var_os("GIT_WORK_TREE") {
+            repo.set_workdir(Path::new(&work_tree), false)
+                .chain_err(|| "error setting the working tree")?;
+        }
+
         Ok(Repo { repo })
     }
 
     pub fn config(&self) -> Result<Config> {
         self.repo
             .config()
-            .map(|config| Config { config })
-            .chain_err(|| "")
+            .map(|config| Config {
+                config,
+                files: vec![(git2::ConfigLevel::Local, self.common_dir().join("config"))],
+            })
+            .chain_err(|| "error opening the repo's git config")
+    }
+
+    /// The repo's config along with the current worktree's own
+    /// `config.worktree`, which takes precedence and is where changes go.
+    pub fn worktree_config(&self) -> Result<Config> {
+        let mut config = self.config()?;
+        let path = self.repo.path().join(WORKTREE_CONFIG);
+        config
+            .config
+            .add_file(&path, git2::ConfigLevel::App, false)
+            .chain_err(|| format!("error opening '{}'", path.display()))?;
+        config.files.push((git2::ConfigLevel::App, path));
+        Ok(config)
+    }
+
+    /// Whether the repo has linked worktrees or already keeps per-worktree
+    /// config.
+    pub fn uses_worktrees(&self) -> bool {
+        self.repo.is_worktree()
+            || self
+                .repo
+                .worktrees()
+                .is_ok_and(|worktrees| !worktrees.is_empty())
+            || self.worktree_config_enabled()
+    }
+
+    fn worktree_config_enabled(&self) -> bool {
+        self.local_config()
+            .ok()
+            .and_then(|config| config.get_bool("extensions.worktreeConfig").ok())
+            .unwrap_or(false)
+    }
+
+    fn enable_worktree_config(&self) -> Result<()> {
+        enable_worktree_config(self.local_config()?)
+    }
+
+    /// Opens a config file at the root of the working tree, creating it if
+    /// it doesn't exist yet.
+    pub fn file_config(&self, filename: &str) -> Result<Config> {
+        let workdir = self.repo.workdir().ok_or("not in a working tree")?;
+        let path = workdir.join(filename);
+        if !path.exists() {
+            fs::File::create(&path).chain_err(|| format!("error creating '{}'", path.display()))?;
+        }
+
+        git2::Config::open(&path)
+            .map(|config| Config {
+                config,
+                files: vec![(git2::ConfigLevel::Local, path.clone())],
+            })
+            .chain_err(|| format!("error opening '{}'", path.display()))
     }
 
     pub fn auto_include(&self, filename: &str) -> Result<()> {
@@ -40,53 +156,294 @@ impl Repo {
             return Ok(());
         }
 
-        let include_paths = self.include_paths()?;
+        // A relative path in the shared config points at the main worktree's
+        // copy, so linked worktrees include their own from their own config
+        let (mut config, include_path) = if self.repo.is_worktree() {
+            let path = self.repo.path().join(WORKTREE_CONFIG);
+            let config = git2::Config::open(&path)
+                .chain_err(|| format!("error opening '{}'", path.display()))?;
+            let include_path = path_buf
+                .to_str()
+                .ok_or_else(|| format!("invalid path: '{}'", path_buf.display()))?
+                .to_string();
+            (config, include_path)
+        } else {
+            (self.local_config()?, include_path)
+        };
+
+        let include_paths = include_paths(&config)?;
         if include_paths.contains(&include_path) {
             return Ok(());
         }
 
-        let mut config = self.local_config()?;
         config
             .set_multivar("include.path", "^$", &include_path)
-            .and(Ok(()))
-            .chain_err(|| "")
+            .chain_err(|| format!("error including '{}' in git config", filename))?;
+        if self.repo.is_worktree() {
+            self.enable_worktree_config()?;
+        }
+        Ok(())
     }
 
-    fn include_paths(&self) -> Result<Vec<String>> {
-        let config = self.local_config()?;
-        let mut include_paths: Vec<String> = Vec::new();
-        config
-            .entries(Some("include.path"))
-            .chain_err(|| "")?
-            .for_each(|entry| {
-                let value = entry.value().unwrap_or("").to_string();
-                include_paths.push(value)
+    pub fn recent_authors(&self, limit: usize) -> Result<Vec<String>> {
+        let mut revwalk = self.repo.revwalk().chain_err(|| "error walking commits")?;
+        if revwalk.push_head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        revwalk
+            .take(limit)
+            .map(|oid| {
+                let oid = oid.chain_err(|| "error walking commits")?;
+                let commit = self
+                    .repo
+                    .find_commit(oid)
+                    .chain_err(|| format!("error finding commit '{}'", oid))?;
+                let email = commit.author().email().unwrap_or("").to_string();
+                Ok(email)
             })
-            .chain_err(|| "")?;
-        Ok(include_paths)
+            .collect()
+    }
+
+    /// Whether the index has changes that aren't in `HEAD`
+    pub fn has_staged_changes(&self) -> Result<bool> {
+        let head = self.repo.head().and_then(|head| head.peel_to_tree()).ok();
+        let diff = self
+            .repo
+            .diff_tree_to_index(head.as_ref(), None, None)
+            .chain_err(|| "error comparing the index to HEAD")?;
+        Ok(diff.deltas().len() > 0)
+    }
+
+    /// The branch `HEAD` points at, even if it has no commits yet
+    pub fn branch(&self) -> Option<String> {
+        let head = self.repo.find_reference("HEAD").ok()?;
+        let target = head.symbolic_target()?;
+        target.strip_prefix("refs/heads/").map(String::from)
+    }
+
+    /// Where git looks for hooks, respecting `core.hooksPath`.
+    pub fn hooks_dir(&self) -> Result<PathBuf> {
+        let config = self
+            .repo
+            .config()
+            .chain_err(|| "error opening git config")?;
+        match config.get_path("core.hooksPath") {
+            Ok(path) if path.is_relative() => {
+                let workdir = self.repo.workdir().unwrap_or_else(|| self.repo.path());
+                Ok(workdir.join(path))
+            }
+            Ok(path) => Ok(path),
+            Err(_) => Ok(self.common_dir().join("hooks")),
+        }
+    }
+
+    /// The git dir shared by all worktrees, which is where linked worktrees
+    /// find hooks and the local config.
+    fn common_dir(&self) -> PathBuf {
+        let path = self.repo.path();
+        fs::read_to_string(path.join("commondir"))
+            .map(|commondir| path.join(commondir.trim()))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    /// Commits reachable from `range` (or `HEAD`), newest first, skipping
+    /// any made before `since`.
+    pub fn commits(&self, range: Option<&str>, since: Option<i64>) -> Result<Vec<history::Commit>> {
+        let mut revwalk = self.repo.revwalk().chain_err(|| "error walking commits")?;
+        revwalk
+            .set_sorting(git2::Sort::TIME)
+            .chain_err(|| "error walking commits")?;
+
+        match range {
+            Some(range) if range.contains("..") => revwalk.push_range(range),
+            Some(rev) => self
+                .repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .and_then(|commit| revwalk.push(commit.id())),
+            None if revwalk.push_head().is_err() => {
+                return Ok(Vec::new());
+            }
+            None => Ok(()),
+        }
+        .chain_err(|| format!("invalid revision: '{}'", range.unwrap_or("HEAD")))?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let oid = oid.chain_err(|| "error walking commits")?;
+            let commit = self
+                .repo
+                .find_commit(oid)
+                .chain_err(|| format!("error finding commit '{}'", oid))?;
+
+            let time = commit.time().seconds();
+            if since.is_some_and(|since| time < since) {
+                continue;
+            }
+
+            commits.push(history::Commit {
+                author: commit.author().email().unwrap_or("").into(),
+                committer: commit.committer().email().unwrap_or("").into(),
+                message: commit.message().unwrap_or("").into(),
+                time,
+            });
+        }
+        Ok(commits)
     }
 
     fn local_config(&self) -> Result<git2::Config> {
-        let config = self.repo.config().chain_err(|| "")?;
-        config.open_level(git2::ConfigLevel::Local).chain_err(|| "")
+        let config = self
+            .repo
+            .config()
+            .chain_err(|| "error opening the repo's git config")?;
+        config
+            .open_level(git2::ConfigLevel::Local)
+            .chain_err(|| "error opening the repo's local git config")
+    }
+}
+
+/// Turns on `extensions.worktreeConfig` in the repo's `local` config so that
+/// git reads each worktree's `config.worktree` too.
+fn enable_worktree_config(mut local: git2::Config) -> Result<()> {
+    if local.get_bool("extensions.worktreeConfig").unwrap_or(false) {
+        return Ok(());
     }
+
+    local
+        .set_bool("extensions.worktreeConfig", true)
+        .chain_err(|| "error setting git config 'extensions.worktreeConfig'")
+}
+
+fn include_paths(config: &git2::Config) -> Result<Vec<String>> {
+    let mut include_paths: Vec<String> = Vec::new();
+    config
+        .entries(Some("include.path"))
+        .chain_err(|| "error getting git config for 'include.path'")?
+        .for_each(|entry| {
+            let value = entry.value().unwrap_or("").to_string();
+            include_paths.push(value)
+        })
+        .chain_err(|| "error getting git config for 'include.path'")?;
+    Ok(include_paths)
 }
 
 pub struct Config {
     config: git2::Config,
+    /// Files behind the levels that git2 doesn't know the path of
+    files: Vec<(git2::ConfigLevel, PathBuf)>,
 }
 
 impl Config {
     pub fn new(scope: ConfigScope) -> Result<Self> {
         let config = match scope {
             ConfigScope::Local => git2::Config::open_default(),
+            ConfigScope::Worktree => return Repo::new()?.worktree_config(),
             ConfigScope::Global => git2::Config::open_default().and_then(|mut r| r.open_global()),
+            ConfigScope::File => {
+                return Repo::new()?.file_config(&format!(".{}", crate::NAMESPACE));
+            }
         };
 
-        config.map(|config| Config { config }).chain_err(|| "")
+        config
+            .map(|config| Config {
+                config,
+                files: Vec::new(),
+            })
+            .chain_err(|| "error opening git config")
+    }
+
+    /// Git only reads `config.worktree` once the repo says so, which is
+    /// left until something is actually written there.
+    fn prepare_write(&self) -> Result<()> {
+        let worktree = self
+            .files
+            .iter()
+            .any(|&(level, _)| level as i32 == git2::ConfigLevel::App as i32);
+        let local = self.file(git2::ConfigLevel::Local);
+        match local {
+            Some(local) if worktree => enable_worktree_config(
+                git2::Config::open(&local)
+                    .chain_err(|| format!("error opening '{}'", local.display()))?,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// The level changes go to, which is the highest one there is
+    fn write_level(&self) -> Option<git2::ConfigLevel> {
+        let levels = [
+            git2::ConfigLevel::App,
+            git2::ConfigLevel::Local,
+            git2::ConfigLevel::Global,
+            git2::ConfigLevel::XDG,
+            git2::ConfigLevel::System,
+            git2::ConfigLevel::ProgramData,
+        ];
+        levels
+            .into_iter()
+            .find(|&level| self.config.open_level(level).is_ok())
+    }
+
+    fn file(&self, level: git2::ConfigLevel) -> Option<PathBuf> {
+        let known = self
+            .files
+            .iter()
+            .find(|&&(known, _)| known as i32 == level as i32)
+            .map(|(_, path)| path.clone());
+        known.or_else(|| match level {
+            git2::ConfigLevel::System => git2::Config::find_system().ok(),
+            git2::ConfigLevel::XDG => git2::Config::find_xdg().ok(),
+            git2::ConfigLevel::Global => git2::Config::find_global().ok(),
+            _ => None,
+        })
+    }
+}
+
+fn level_name(level: git2::ConfigLevel) -> &'static str {
+    match level {
+        git2::ConfigLevel::ProgramData => "programdata",
+        git2::ConfigLevel::System => "system",
+        git2::ConfigLevel::XDG => "xdg",
+        git2::ConfigLevel::Global => "global",
+        git2::ConfigLevel::Local => "local",
+        // only ever used for `config.worktree`
+        git2::ConfigLevel::App => "worktree",
+        git2::ConfigLevel::Highest => "highest",
     }
 }
 
+/// Where an `include.path` in `file` points, like git resolves it
+fn resolve_include(file: &Path, include: &str) -> PathBuf {
+    let path = match include.strip_prefix("~/") {
+        Some(rest) => env::var_os("HOME")
+            .map(|home| Path::new(&home).join(rest))
+            .unwrap_or_else(|| PathBuf::from(include)),
+        None => file
+            .parent()
+            .map(|dir| dir.join(include))
+            .unwrap_or_else(|| PathBuf::from(include)),
+    };
+
+    // `.git/../.git-together` reads better as `.git-together`
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir
+                if matches!(
+                    resolved.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
 impl config::Config for Config {
     fn get(&self, name: &str) -> Result<String> {
         self.config
@@ -94,6 +451,66 @@ impl config::Config for Config {
             .chain_err(|| format!("error getting git config for '{}'", name))
     }
 
+    fn scope(&self, name: &str) -> Result<String> {
+        let entry = self
+            .config
+            .get_entry(name)
+            .chain_err(|| format!("error getting git config for '{}'", name))?;
+        Ok(level_name(entry.level()).into())
+    }
+
+    fn origin(&self, name: &str) -> Result<String> {
+        let entry = self
+            .config
+            .get_entry(name)
+            .chain_err(|| format!("error getting git config for '{}'", name))?;
+        let level = entry.level();
+        let file = match self.file(level) {
+            Some(file) => file,
+            None => return Ok(format!("{} config", level_name(level))),
+        };
+        if entry.include_depth() == 0 {
+            return Ok(file.display().to_string());
+        }
+
+        // Only a lone include can be pinned down as the file
+        let mut includes = Vec::new();
+        if entry.include_depth() == 1 {
+            if let Ok(config) = git2::Config::open(&file) {
+                if let Ok(entries) = config.multivar("include.path", None) {
+                    let _ = entries.for_each(|entry| {
+                        if let (0, Some(value)) = (entry.include_depth(), entry.value()) {
+                            includes.push(value.to_string());
+                        }
+                    });
+                }
+            }
+        }
+        match includes.as_slice() {
+            [include] => Ok(resolve_include(&file, include).display().to_string()),
+            _ => Ok(format!("a file included from {}", file.display())),
+        }
+    }
+
+    fn writable(&self, name: &str) -> bool {
+        match (self.config.get_entry(name), self.write_level()) {
+            (Ok(entry), Some(level)) => {
+                entry.include_depth() == 0 && entry.level() as i32 == level as i32
+            }
+            _ => false,
+        }
+    }
+
+    fn shadowable(&self, name: &str) -> bool {
+        // libgit2 won't set a key that's included into the file it writes to
+        match (self.config.get_entry(name), self.write_level()) {
+            (Ok(entry), Some(level)) => {
+                entry.include_depth() == 0 || entry.level() as i32 != level as i32
+            }
+            _ => true,
+        }
+    }
+
     fn get_all(&self, glob: &str) -> Result<HashMap<String, String>> {
         let mut result = HashMap::new();
         let entries = self
@@ -106,17 +523,41 @@ impl config::Config for Config {
                     result.insert(name.into(), value.into());
                 }
             })
-            .chain_err(|| "")?;
+            .chain_err(|| "error getting git config entries")?;
         Ok(result)
     }
 
+    fn get_multi(&self, name: &str) -> Result<Vec<String>> {
+        let mut values = Vec::new();
+        let entries = self
+            .config
+            .multivar(name, None)
+            .chain_err(|| format!("error getting git config for '{}'", name))?;
+        entries
+            .for_each(|entry| {
+                if let Some(value) = entry.value() {
+                    values.push((entry.level(), value.to_string()));
+                }
+            })
+            .chain_err(|| format!("error getting git config for '{}'", name))?;
+
+        let highest = values.iter().map(|&(level, _)| level as i32).max();
+        Ok(values
+            .into_iter()
+            .filter(|&(level, _)| Some(level as i32) == highest)
+            .map(|(_, value)| value)
+            .collect())
+    }
+
     fn add(&mut self, name: &str, value: &str) -> Result<()> {
+        self.prepare_write()?;
         self.config
             .set_multivar(name, "^$", value)
             .chain_err(|| format!("error adding git config '{}': '{}'", name, value))
     }
 
     fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        self.prepare_write()?;
         self.config
             .set_str(name, value)
             .chain_err(|| format!("error setting git config '{}': '{}'", name, value))
@@ -127,4 +568,47 @@ impl config::Config for Config {
             .remove(name)
             .chain_err(|| format!("error removing git config '{}'", name))
     }
+
+    fn clear_all(&mut self, name: &str) -> Result<()> {
+        match self.config.remove_multivar(name, ".*") {
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => Ok(()),
+            result => result.chain_err(|| format!("error removing git config '{}'", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary() {
+        let dir = env::temp_dir().join(format!("git-together-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let git = dir.join(format!("git{}", env::consts::EXE_SUFFIX));
+        fs::write(&git, "").unwrap();
+        let path = dir.clone().into_os_string();
+
+        assert_eq!(find_binary(None, None, &path).unwrap(), git);
+        assert_eq!(
+            find_binary(Some("".into()), Some("".into()), &path).unwrap(),
+            git
+        );
+        assert_eq!(
+            find_binary(None, Some("/opt/git/bin/git".into()), &path).unwrap(),
+            PathBuf::from("/opt/git/bin/git")
+        );
+        assert_eq!(
+            find_binary(
+                Some("/usr/local/bin/git".into()),
+                Some("/opt/git/bin/git".into()),
+                &path
+            )
+            .unwrap(),
+            PathBuf::from("/usr/local/bin/git")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(find_binary(None, None, &path).is_err());
+    }
 }
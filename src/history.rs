@@ -0,0 +1,452 @@
+use std::collections::{BTreeMap, HashMap};
+
+const TRAILERS: [&str; 2] = ["signed-off-by", "co-authored-by"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commit {
+    pub author: String,
+    pub committer: String,
+    pub message: String,
+    pub time: i64,
+}
+
+impl Commit {
+    /// Emails of everyone credited on the commit, in lowercase.
+    pub fn emails(&self) -> Vec<String> {
+        let trailers = self.message.lines().filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if !TRAILERS.contains(&key.trim().to_lowercase().as_str()) {
+                return None;
+            }
+
+            let start = value.find('<')?;
+            let end = value.rfind('>')?;
+            value
+                .get(start + 1..end)
+                .map(str::trim)
+                .map(str::to_lowercase)
+        });
+
+        vec![self.author.to_lowercase(), self.committer.to_lowercase()]
+            .into_iter()
+            .chain(trailers)
+            .collect()
+    }
+
+    /// Sorted initials of everyone on the roster credited on the commit,
+    /// where the roster maps lowercase emails to initials.
+    pub fn pair(&self, roster: &HashMap<String, String>) -> Vec<String> {
+        let mut pair: Vec<_> = self
+            .emails()
+            .iter()
+            .filter_map(|email| roster.get(email))
+            .cloned()
+            .collect();
+        pair.sort();
+        pair.dedup();
+        pair
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Tally {
+    pub pairs: BTreeMap<Vec<String>, usize>,
+    pub people: BTreeMap<String, usize>,
+}
+
+impl Tally {
+    pub fn new(commits: &[Commit], roster: &HashMap<String, String>) -> Self {
+        let mut tally = Tally::default();
+        for commit in commits {
+            let pair = commit.pair(roster);
+            if pair.is_empty() {
+                continue;
+            }
+
+            for initials in &pair {
+                *tally.people.entry(initials.clone()).or_insert(0) += 1;
+            }
+            *tally.pairs.entry(pair).or_insert(0) += 1;
+        }
+        tally
+    }
+}
+
+/// How many commits each combination of authors made together, where the
+/// diagonal counts solo commits.
+#[derive(Debug, PartialEq)]
+pub struct Stairs {
+    pub initials: Vec<String>,
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl Stairs {
+    pub fn new(commits: &[Commit], roster: &HashMap<String, String>) -> Self {
+        let mut initials: Vec<_> = roster.values().cloned().collect();
+        initials.sort();
+        initials.dedup();
+
+        let index: HashMap<_, _> = initials.iter().enumerate().map(|(i, x)| (x, i)).collect();
+        let mut counts = vec![vec![0; initials.len()]; initials.len()];
+        for commit in commits {
+            let pair: Vec<_> = commit.pair(roster).iter().map(|x| index[x]).collect();
+            match pair.as_slice() {
+                [solo] => counts[*solo][*solo] += 1,
+                _ => {
+                    for (n, &i) in pair.iter().enumerate() {
+                        for &j in &pair[n + 1..] {
+                            counts[i][j] += 1;
+                            counts[j][i] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        Stairs { initials, counts }
+    }
+
+    /// The rows of the lower triangle, each headed by its initials.
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.initials
+            .iter()
+            .enumerate()
+            .map(|(i, initials)| {
+                let counts = self.counts[i][..=i].iter().map(usize::to_string);
+                std::iter::once(initials.clone()).chain(counts).collect()
+            })
+            .collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let width = self
+            .initials
+            .iter()
+            .map(String::len)
+            .chain(self.counts.iter().flatten().map(|n| n.to_string().len()))
+            .max()
+            .unwrap_or(0);
+
+        let header = std::iter::once(String::new())
+            .chain(self.initials.iter().cloned())
+            .map(|cell| format!("{:>width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let rows = self.rows().into_iter().map(|row| {
+            row.iter()
+                .map(|cell| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+
+        std::iter::once(header)
+            .chain(rows)
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let header = format!("| | {} |\n", self.initials.join(" | "));
+        let rule = format!("|---|{}\n", "---|".repeat(self.initials.len()));
+        let rows = self.rows().into_iter().map(|mut row| {
+            row.resize(self.initials.len() + 1, String::new());
+            format!("| {} |\n", row.join(" | "))
+        });
+
+        std::iter::once(header)
+            .chain(std::iter::once(rule))
+            .chain(rows)
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let header = format!(",{}\n", self.initials.join(","));
+        let rows = self.rows().into_iter().map(|mut row| {
+            row.resize(self.initials.len() + 1, String::new());
+            format!("{}\n", row.join(","))
+        });
+
+        std::iter::once(header).chain(rows).collect()
+    }
+}
+
+/// Pairs up `present` so that everyone works with the people they've paired
+/// with least recently, leaving one person solo if there's an odd number.
+pub fn suggest(
+    commits: &[Commit],
+    roster: &HashMap<String, String>,
+    present: &[&str],
+) -> Vec<Vec<String>> {
+    let mut last_paired: HashMap<(String, String), i64> = HashMap::new();
+    for commit in commits {
+        let pair = commit.pair(roster);
+        for (n, a) in pair.iter().enumerate() {
+            for b in &pair[n + 1..] {
+                let time = last_paired.entry((a.clone(), b.clone())).or_insert(0);
+                *time = (*time).max(commit.time);
+            }
+        }
+    }
+
+    let last_paired = |a: &str, b: &str| {
+        let key = if a < b { (a, b) } else { (b, a) };
+        last_paired
+            .get(&(key.0.to_string(), key.1.to_string()))
+            .cloned()
+            .unwrap_or(0)
+    };
+
+    let costs: Vec<Vec<i64>> = present
+        .iter()
+        .map(|a| present.iter().map(|b| last_paired(a, b)).collect())
+        .collect();
+    let pairs = if present.len() <= EXACT_LIMIT {
+        best_pairs(&costs)
+    } else {
+        greedy_pairs(&costs)
+    };
+    pairs
+        .into_iter()
+        .map(|pair| pair.into_iter().map(|n| present[n].to_string()).collect())
+        .collect()
+}
+
+/// Past this many people an exact search takes too long, so suggestions fall
+/// back to taking the stalest pairs first.
+const EXACT_LIMIT: usize = 16;
+
+/// Finds the pairing with the oldest total last-paired time, given the
+/// last-paired time of every two people in `costs`. Works through every
+/// subset of people, smallest first, remembering the best way to pair up each
+/// one by who the first person in it works with, if anyone.
+fn best_pairs(costs: &[Vec<i64>]) -> Vec<Vec<usize>> {
+    let everyone = (1usize << costs.len()) - 1;
+    let mut best: Vec<(i64, Option<usize>)> = vec![(0, None); everyone + 1];
+    for people in 1..=everyone {
+        let first = people.trailing_zeros() as usize;
+        let rest = people & !(1 << first);
+
+        // Only an odd number of people leaves someone solo
+        let mut choice = (people.count_ones() % 2 == 1).then(|| (best[rest].0, None));
+        for partner in (first + 1..costs.len()).filter(|&n| rest & (1 << n) != 0) {
+            let score = best[rest & !(1 << partner)].0 + costs[first][partner];
+            if choice.is_none_or(|(best, _)| score < best) {
+                choice = Some((score, Some(partner)));
+            }
+        }
+        best[people] = choice.unwrap_or_default();
+    }
+
+    let mut pairs = Vec::new();
+    let mut people = everyone;
+    while people != 0 {
+        let first = people.trailing_zeros() as usize;
+        let pair = match best[people].1 {
+            Some(partner) => vec![first, partner],
+            None => vec![first],
+        };
+        for n in &pair {
+            people &= !(1 << n);
+        }
+        pairs.push(pair);
+    }
+    pairs
+}
+
+/// Pairs people up by taking the stalest pairs first, which isn't always the
+/// best pairing overall but scales to any number of people.
+fn greedy_pairs(costs: &[Vec<i64>]) -> Vec<Vec<usize>> {
+    let mut candidates: Vec<_> = (0..costs.len())
+        .flat_map(|a| (a + 1..costs.len()).map(move |b| (a, b)))
+        .collect();
+    candidates.sort_by_key(|&(a, b)| costs[a][b]);
+
+    let mut paired = vec![false; costs.len()];
+    let mut pairs = Vec::new();
+    for (a, b) in candidates {
+        if !paired[a] && !paired[b] {
+            paired[a] = true;
+            paired[b] = true;
+            pairs.push(vec![a, b]);
+        }
+    }
+    pairs.extend((0..costs.len()).filter(|&n| !paired[n]).map(|n| vec![n]));
+    pairs.sort();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster() -> HashMap<String, String> {
+        [
+            ("jholden@rocinante.com", "jh"),
+            ("nnagata@rocinante.com", "nn"),
+            ("avasarala@un.gov", "ca"),
+        ]
+        .iter()
+        .map(|&(email, initials)| (email.into(), initials.into()))
+        .collect()
+    }
+
+    fn commit(author: &str, committer: &str, message: &str) -> Commit {
+        Commit {
+            author: author.into(),
+            committer: committer.into(),
+            message: message.into(),
+            time: 0,
+        }
+    }
+
+    #[test]
+    fn pair() {
+        let roster = roster();
+
+        let solo = commit("jholden@rocinante.com", "jholden@rocinante.com", "add foo");
+        assert_eq!(solo.pair(&roster), vec!["jh"]);
+
+        let signoff = commit(
+            "nnagata@rocinante.com",
+            "JHolden@rocinante.com",
+            "add foo\n\nSigned-off-by: James Holden <jholden@rocinante.com>",
+        );
+        assert_eq!(signoff.pair(&roster), vec!["jh", "nn"]);
+
+        let coauthors = commit(
+            "jholden@rocinante.com",
+            "jholden@rocinante.com",
+            "add foo\n\n\
+             Co-authored-by: Naomi Nagata <nnagata@rocinante.com>\n\
+             co-authored-by: Chrisjen Avasarala <avasarala@un.gov>\n\
+             Co-authored-by: Amos Burton <aburton@rocinante.com>",
+        );
+        assert_eq!(coauthors.pair(&roster), vec!["ca", "jh", "nn"]);
+
+        let stranger = commit("aburton@rocinante.com", "noreply@github.com", "add foo");
+        assert!(stranger.pair(&roster).is_empty());
+    }
+
+    #[test]
+    fn tally() {
+        let commits = vec![
+            commit("jholden@rocinante.com", "nnagata@rocinante.com", "add foo"),
+            commit("nnagata@rocinante.com", "jholden@rocinante.com", "add bar"),
+            commit("nnagata@rocinante.com", "nnagata@rocinante.com", "add baz"),
+            commit("aburton@rocinante.com", "aburton@rocinante.com", "add qux"),
+        ];
+        let tally = Tally::new(&commits, &roster());
+
+        let pairs: Vec<_> = tally.pairs.into_iter().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (vec!["jh".to_string(), "nn".to_string()], 2),
+                (vec!["nn".to_string()], 1),
+            ]
+        );
+
+        let people: Vec<_> = tally.people.into_iter().collect();
+        assert_eq!(people, vec![("jh".into(), 2), ("nn".into(), 3)]);
+    }
+
+    #[test]
+    fn suggest() {
+        let mut roster = roster();
+        roster.insert("akamal@rocinante.com".into(), "ak".into());
+
+        let commits = vec![
+            Commit {
+                time: 300,
+                ..commit("jholden@rocinante.com", "nnagata@rocinante.com", "")
+            },
+            Commit {
+                time: 200,
+                ..commit("avasarala@un.gov", "akamal@rocinante.com", "")
+            },
+            Commit {
+                time: 100,
+                ..commit("jholden@rocinante.com", "avasarala@un.gov", "")
+            },
+            Commit {
+                time: 100,
+                ..commit("nnagata@rocinante.com", "akamal@rocinante.com", "")
+            },
+        ];
+
+        assert_eq!(
+            super::suggest(&commits, &roster, &["jh", "nn", "ca", "ak"]),
+            vec![vec!["jh", "ak"], vec!["nn", "ca"]]
+        );
+        assert_eq!(
+            super::suggest(&commits, &roster, &["jh", "nn", "ca"]),
+            vec![vec!["jh"], vec!["nn", "ca"]]
+        );
+        assert_eq!(
+            super::suggest(&[], &roster, &["jh", "nn"]),
+            vec![vec!["jh", "nn"]]
+        );
+        assert!(super::suggest(&commits, &roster, &[]).is_empty());
+    }
+
+    #[test]
+    fn suggest_large_teams() {
+        let initials: Vec<_> = (0..41).map(|n| format!("p{}", n)).collect();
+        for size in [EXACT_LIMIT, EXACT_LIMIT + 1, 41] {
+            let present: Vec<_> = initials[..size].iter().map(String::as_ref).collect();
+            let pairs = super::suggest(&[], &HashMap::new(), &present);
+
+            assert_eq!(pairs.len(), size.div_ceil(2));
+            let mut people: Vec<_> = pairs.into_iter().flatten().collect();
+            people.sort();
+            people.dedup();
+            assert_eq!(people.len(), size);
+        }
+    }
+
+    #[test]
+    fn stairs() {
+        let commits = vec![
+            commit("jholden@rocinante.com", "nnagata@rocinante.com", "add foo"),
+            commit("nnagata@rocinante.com", "jholden@rocinante.com", "add bar"),
+            commit("nnagata@rocinante.com", "nnagata@rocinante.com", "add baz"),
+            commit(
+                "avasarala@un.gov",
+                "avasarala@un.gov",
+                "add qux\n\n\
+                 Co-authored-by: James Holden <jholden@rocinante.com>\n\
+                 Co-authored-by: Naomi Nagata <nnagata@rocinante.com>",
+            ),
+        ];
+        let stairs = Stairs::new(&commits, &roster());
+
+        assert_eq!(stairs.initials, vec!["ca", "jh", "nn"]);
+        assert_eq!(
+            stairs.counts,
+            vec![vec![0, 1, 1], vec![1, 0, 3], vec![1, 3, 1]]
+        );
+
+        assert_eq!(
+            stairs.to_text(),
+            "   ca jh nn\n\
+             ca  0\n\
+             jh  1  0\n\
+             nn  1  3  1\n"
+        );
+        assert_eq!(
+            stairs.to_markdown(),
+            "| | ca | jh | nn |\n\
+             |---|---|---|---|\n\
+             | ca | 0 |  |  |\n\
+             | jh | 1 | 0 |  |\n\
+             | nn | 1 | 3 | 1 |\n"
+        );
+        assert_eq!(
+            stairs.to_csv(),
+            ",ca,jh,nn\n\
+             ca,0,,\n\
+             jh,1,0,\n\
+             nn,1,3,1\n"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::author::Author;
+use crate::errors::*;
+
+const HOOKS: [&str; 3] = ["prepare-commit-msg", "commit-msg", "post-commit"];
+const MARKER: &str = "# Installed by git-together";
+
+/// Runs the hook with the binary that installed it, which may not be on
+/// `PATH` under its own name when it's installed as `git`.
+fn script(exe: &Path, name: &str) -> String {
+    let exe = exe.display().to_string().replace('\'', "'\\''");
+    format!(
+        "#!/bin/sh\n{}\nexec '{}' together hook {} \"$@\"\n",
+        MARKER, exe, name
+    )
+}
+
+/// Writes the hooks into `dir`, refusing to replace hooks that git-together
+/// didn't install unless forced.
+pub fn install(dir: &Path, force: bool) -> Result<()> {
+    let exe = env::current_exe().chain_err(|| "error finding the git-together binary")?;
+    fs::create_dir_all(dir).chain_err(|| format!("error creating '{}'", dir.display()))?;
+
+    for name in HOOKS.iter() {
+        let path = dir.join(name);
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        if !force && !existing.is_empty() && !existing.contains(MARKER) {
+            return Err(format!(
+                "'{}' already exists (use --force to replace it)",
+                path.display()
+            )
+            .into());
+        }
+    }
+
+    for name in HOOKS.iter() {
+        let path = dir.join(name);
+        fs::write(&path, script(&exe, name))
+            .chain_err(|| format!("error writing '{}'", path.display()))?;
+        make_executable(&path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .chain_err(|| format!("error making '{}' executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// The author of the commit being made, which git hands to hooks when
+/// it's run by `git commit` and works out from config otherwise.
+pub fn commit_author(git: &Path) -> Result<Author> {
+    if let (Ok(name), Ok(email)) = (env::var("GIT_AUTHOR_NAME"), env::var("GIT_AUTHOR_EMAIL")) {
+        return Ok(Author {
+            name,
+            email,
+            fields: Default::default(),
+        });
+    }
+
+    let output = Command::new(git)
+        .args(["var", "GIT_AUTHOR_IDENT"])
+        .output()
+        .chain_err(|| "failed to execute process")?;
+    let ident = String::from_utf8_lossy(&output.stdout);
+    parse_ident(&ident).ok_or_else(|| "error finding the commit's author".into())
+}
+
+/// Splits `Name <email> 1595894400 -0700` into the name and email.
+fn parse_ident(ident: &str) -> Option<Author> {
+    let (name, rest) = ident.split_once('<')?;
+    let (email, _) = rest.split_once('>')?;
+    Some(Author {
+        name: name.trim().into(),
+        email: email.into(),
+        fields: Default::default(),
+    })
+}
+
+/// Adds trailers to the commit message in `file`, skipping any that are
+/// already there.
+pub fn add_trailers(git: &Path, file: &str, trailers: &[String]) -> Result<()> {
+    if trailers.is_empty() {
+        return Ok(());
+    }
+
+    let mut cmd = Command::new(git);
+    cmd.args([
+        "interpret-trailers",
+        "--in-place",
+        "--if-exists",
+        "addIfDifferent",
+    ]);
+    for trailer in trailers {
+        cmd.arg("--trailer").arg(trailer);
+    }
+
+    let status = cmd
+        .arg(file)
+        .status()
+        .chain_err(|| "failed to execute process")?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("error adding trailers to '{}'", file).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts() {
+        assert_eq!(
+            script(Path::new("/usr/local/bin/git"), "commit-msg"),
+            "#!/bin/sh\n# Installed by git-together\nexec '/usr/local/bin/git' together hook commit-msg \"$@\"\n"
+        );
+        assert!(script(Path::new("/home/o'neil/git"), "commit-msg")
+            .contains("exec '/home/o'\\''neil/git' together"));
+    }
+
+    #[test]
+    fn idents() {
+        let author =
+            parse_ident("James Holden <jholden@rocinante.com> 1595894400 -0700\n").unwrap();
+        assert_eq!(author.name, "James Holden");
+        assert_eq!(author.email, "jholden@rocinante.com");
+        assert!(parse_ident("").is_none());
+    }
+}
@@ -6,34 +6,557 @@ extern crate git2;
 pub mod author;
 pub mod config;
 pub mod errors;
+pub mod format;
 pub mod git;
+pub mod history;
+pub mod hooks;
+pub mod time;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::process::Command;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
 
 use author::{Author, AuthorParser};
 use config::Config;
 use errors::*;
+use format::{Format, Json, StairsFormat};
 
 const NAMESPACE: &str = "git-together";
 const TRIGGERS: [&str; 2] = ["with", "together"];
+const RECENT_COMMITS: usize = 50;
+const MAX_DEPTH: u32 = 16;
+const WARN_AFTER: i64 = 12 * 60 * 60;
+const HISTORY_LIMIT: usize = 50;
+const NO_ACTIVE_PAIR: &str = "no active pair (run `git with <initials>` first)";
 
 fn namespaced(name: &str) -> String {
     format!("{}.{}", NAMESPACE, name)
 }
 
-pub fn run() -> Result<i32> {
-    let all_args: Vec<_> = env::args().skip(1).collect();
-    let mut args: Vec<&str> = all_args.iter().map(String::as_ref).collect();
+fn branch_key(branch: &str) -> String {
+    namespaced(&format!("branch.{}.active", branch))
+}
+
+fn author_key(initials: &str) -> String {
+    namespaced(&format!("authors.{}", initials))
+}
+
+/// Where a field of an author in the structured form lives, as in
+/// `[git-together "author.jh"]`
+fn author_field_key(initials: &str, field: &str) -> String {
+    namespaced(&format!("author.{}.{}", initials, field))
+}
+
+fn group_key(name: &str) -> String {
+    namespaced(&format!("groups.{}", name))
+}
+
+fn split_group(raw: &str) -> Vec<String> {
+    raw.split('+')
+        .map(str::trim)
+        .filter(|member| !member.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Trailers crediting everyone in the pair besides the author.
+fn trailers(attribution: Attribution, authors: &[Author]) -> Vec<String> {
+    let (author, others) = match authors.split_first() {
+        Some(split) => split,
+        None => return Vec::new(),
+    };
+
+    let others = others.iter().filter(|&other| other != author);
+    match attribution {
+        Attribution::Signoff => others
+            .take(1)
+            .map(|committer| format!("Signed-off-by: {}", committer))
+            .collect(),
+        Attribution::Coauthors => others
+            .map(|coauthor| format!("Co-authored-by: {}", coauthor))
+            .collect(),
+    }
+}
+
+fn validate_initials(initials: &str) -> Result<()> {
+    let valid = !initials.is_empty()
+        && !initials.starts_with('-')
+        && initials
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid initials: '{}'", initials).into())
+    }
+}
+
+fn rotate<C: config::Config>(gt: &mut GitTogether<C>) -> Result<()> {
+    let recent = match gt.rotation()? {
+        Rotation::LeastRecent => git::Repo::new()?.recent_authors(RECENT_COMMITS)?,
+        _ => Vec::new(),
+    };
+    gt.rotate_active(&recent)
+}
+
+fn run_hook<C: config::Config>(gt: &mut GitTogether<C>, name: &str, args: &[&str]) -> Result<()> {
+    // Commits made through git-together are already attributed, and rebases
+    // shouldn't credit the current pair for old commits
+    let rebasing = env::var("GIT_REFLOG_ACTION").is_ok_and(|action| action.starts_with("rebase"));
+    if env::var("GIT_TOGETHER_SIGNOFF").is_ok() || rebasing || gt.is_paused() {
+        return Ok(());
+    }
+
+    match (name, args) {
+        ("prepare-commit-msg", [_, "merge", ..]) => Ok(()),
+        ("prepare-commit-msg", [file, ..]) => {
+            if !gt.check_active()? {
+                return Ok(());
+            }
+            gt.check_session(time::Timestamp::now()?)?;
+            let git = git::binary(gt.git_path())?;
+            let author = hooks::commit_author(&git)?;
+            hooks::add_trailers(&git, file, &gt.hook_trailers(&author)?)
+        }
+        _ if gt.get_active().is_err() => Ok(()),
+        ("commit-msg", [file, ..]) => {
+            let git = git::binary(gt.git_path())?;
+            let author = hooks::commit_author(&git)?;
+            hooks::add_trailers(&git, file, &gt.hook_trailers(&author)?)
+        }
+        ("post-commit", _) => rotate(gt),
+        _ => Err(format!("unknown hook: '{}'", name).into()),
+    }
+}
 
-    let mut gt = if args.contains(&"--global") {
-        GitTogether::new(ConfigScope::Global)
+/// Splits the arguments of history commands into a revision range and a
+/// `--since` cutoff.
+fn log_args<'a>(args: &[&'a str]) -> Result<(Option<&'a str>, Option<i64>)> {
+    let mut range = None;
+    let mut since = None;
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--since" => {
+                let raw = args.next().ok_or("missing value for --since")?;
+                since = Some(time::parse_since(raw, time::Timestamp::now()?)?);
+            }
+            _ if arg.starts_with('-') || range.is_some() => {
+                return Err(format!("unexpected argument: '{}'", arg).into());
+            }
+            _ => range = Some(arg),
+        }
+    }
+    Ok((range, since))
+}
+
+/// Pulls `name <value>` out of the arguments, returning the value and the
+/// remaining arguments.
+fn take_option<'a>(args: &[&'a str], name: &str) -> Result<(Option<&'a str>, Vec<&'a str>)> {
+    match args.iter().position(|&arg| arg == name) {
+        Some(i) => {
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| format!("missing value for {}", name))?;
+            let rest = args[..i].iter().chain(&args[i + 2..]).cloned().collect();
+            Ok((Some(value), rest))
+        }
+        None => Ok((None, args.to_vec())),
+    }
+}
+
+/// Pulls every `name <value>` out of the arguments.
+fn take_options<'a>(args: &[&'a str], name: &str) -> Result<(Vec<&'a str>, Vec<&'a str>)> {
+    let mut values = Vec::new();
+    let mut rest = args.to_vec();
+    while let (Some(value), remaining) = take_option(&rest, name)? {
+        values.push(value);
+        rest = remaining;
+    }
+    Ok((values, rest))
+}
+
+/// Pulls people joining (`--join ak` or `+ak`) and leaving (`--leave nn` or
+/// `-nn`) the active pair out of the arguments.
+fn take_membership<'a>(args: &[&'a str]) -> Result<(Vec<&'a str>, Vec<&'a str>, Vec<&'a str>)> {
+    let (mut joining, args) = take_options(args, "--join")?;
+    let (mut leaving, args) = take_options(&args, "--leave")?;
+
+    let mut rest = Vec::new();
+    for arg in args {
+        match (arg.strip_prefix('+'), arg.strip_prefix('-')) {
+            (Some(initials), _) if !initials.is_empty() => joining.push(initials),
+            (_, Some(initials)) if !initials.is_empty() && !initials.starts_with('-') => {
+                leaving.push(initials)
+            }
+            _ => rest.push(arg),
+        }
+    }
+    Ok((joining, leaving, rest))
+}
+
+fn validate_trailer(trailer: &str) -> Result<()> {
+    let valid = match trailer.split_once(':') {
+        Some((key, value)) => {
+            !key.is_empty() && !key.contains(char::is_whitespace) && !value.trim().is_empty()
+        }
+        None => false,
+    };
+    if trailer.contains(char::is_control) {
+        Err(format!(
+            "invalid trailer: '{}' (contains control characters)",
+            trailer.escape_debug()
+        )
+        .into())
+    } else if valid {
+        Ok(())
     } else {
-        GitTogether::new(ConfigScope::Local)
-    }?;
+        Err(format!("invalid trailer: '{}' (expected 'Key: value')", trailer).into())
+    }
+}
+
+fn by_count<K: Ord>(counts: &BTreeMap<K, usize>) -> Vec<(&K, usize)> {
+    let mut sorted: Vec<_> = counts.iter().map(|(key, &count)| (key, count)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    sorted
+}
+
+/// Prints authors for `git with`, where `text` is the plain text line for
+/// each one and `active` adds the session trailers and nests the authors
+/// under the active pair in JSON.
+fn print_authors<C: Config>(
+    gt: &GitTogether<C>,
+    format: Format,
+    authors: &[(&str, &Author)],
+    active: bool,
+    text: impl Fn(&str, &Author) -> String,
+) -> Result<()> {
+    match format {
+        Format::Text => {
+            if let (true, Some(branch)) = (active, gt.active_branch()) {
+                println!("pair for branch '{}'", branch);
+            }
+            if active && gt.is_paused() {
+                println!("paused (git with --resume to continue)");
+            }
+            for &(initials, author) in authors {
+                println!("{}", text(initials, author));
+            }
+            if active {
+                for trailer in gt.session_trailers()? {
+                    println!("{}", trailer);
+                }
+            } else {
+                for (name, members) in gt.groups()? {
+                    println!("@{}: {}", name, members.join("+"));
+                }
+            }
+        }
+        Format::Template(template) => {
+            for &(initials, author) in authors {
+                let scope = gt.author_scope(initials)?;
+                println!("{}", format::template(template, initials, author, &scope));
+            }
+        }
+        Format::Json => {
+            let authors = authors
+                .iter()
+                .map(|&(initials, author)| {
+                    let scope = gt.author_scope(initials)?;
+                    Ok(format::author_json(initials, author, &scope))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let json = if active {
+                let scope = gt.active_scope().map_or(Json::Null, Json::String);
+                let trailers = gt
+                    .session_trailers()?
+                    .into_iter()
+                    .map(Json::String)
+                    .collect();
+                Json::Object(vec![
+                    ("active", Json::Array(authors)),
+                    ("scope", scope),
+                    (
+                        "branch",
+                        gt.active_branch()
+                            .map_or(Json::Null, |branch| Json::String(branch.into())),
+                    ),
+                    ("paused", Json::Bool(gt.is_paused())),
+                    ("trailers", Json::Array(trailers)),
+                ])
+            } else {
+                let groups = gt
+                    .groups()?
+                    .into_iter()
+                    .map(|(name, members)| {
+                        Json::Object(vec![
+                            ("name", Json::String(name)),
+                            (
+                                "members",
+                                Json::Array(members.into_iter().map(Json::String).collect()),
+                            ),
+                        ])
+                    })
+                    .collect();
+                Json::Object(vec![
+                    ("authors", Json::Array(authors)),
+                    ("groups", Json::Array(groups)),
+                ])
+            };
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+/// Applies git's `-C`, `--git-dir` and `--work-tree` global arguments to
+/// this process so that the repo is found where git will look for it.
+fn apply_location(global_args: &[&str]) -> Result<()> {
+    let mut args = global_args.iter();
+    while let Some(&arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name @ ("--git-dir" | "--work-tree"), value)) => (name, value),
+            _ if ["-C", "--git-dir", "--work-tree"].contains(&arg) => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {}", arg))?;
+                (arg, *value)
+            }
+            _ => continue,
+        };
+
+        let path = env::current_dir()
+            .chain_err(|| "error getting the current directory")?
+            .join(value);
+        match name {
+            // Like git, an empty `-C` leaves the directory alone
+            "-C" if value.is_empty() => {}
+            "-C" => {
+                env::set_current_dir(&path).chain_err(|| format!("cannot change to '{}'", value))?
+            }
+            "--git-dir" => env::set_var("GIT_DIR", path),
+            _ => env::set_var("GIT_WORK_TREE", path),
+        }
+    }
+    Ok(())
+}
+
+/// The default email domain, checked up front so that a bad one is blamed on
+/// the config it came from rather than on every author.
+fn domain<C: Config>(config: &C) -> Result<Option<String>> {
+    let key = namespaced("domain");
+    let domain = match config.get(&key) {
+        Ok(domain) => domain,
+        Err(_) => return Ok(None),
+    };
+
+    author::validate_domain(&domain).chain_err(|| invalid_config(config, &key))?;
+    Ok(Some(domain))
+}
+
+/// Names the config key and file behind a bad value
+fn invalid_config<C: Config>(config: &C, key: &str) -> String {
+    match config.origin(key) {
+        Ok(origin) => format!("invalid {} in {}", key, origin),
+        Err(_) => format!("invalid {}", key),
+    }
+}
+
+/// Prints the most recent changes to the active authors first.
+fn print_history(history: &[Change], format: Format, now: time::Timestamp) -> Result<()> {
+    let inits = |inits: &[String]| match inits {
+        [] => "(nobody)".to_string(),
+        inits => inits.join("+"),
+    };
+
+    match format {
+        Format::Text => {
+            for change in history.iter().rev() {
+                println!(
+                    "{:>4} ago  {:<8}  {} -> {}",
+                    time::format_duration(now.seconds - change.time),
+                    change.cause.as_str(),
+                    inits(&change.previous),
+                    inits(&change.active)
+                );
+            }
+        }
+        Format::Json => {
+            let inits = |inits: &[String]| {
+                Json::Array(inits.iter().map(|i| Json::String(i.clone())).collect())
+            };
+            let changes = history
+                .iter()
+                .rev()
+                .map(|change| {
+                    Json::Object(vec![
+                        ("time", Json::Number(change.time)),
+                        ("cause", Json::String(change.cause.as_str().into())),
+                        ("previous", inits(&change.previous)),
+                        ("active", inits(&change.active)),
+                    ])
+                })
+                .collect();
+            println!("{}", Json::Object(vec![("history", Json::Array(changes))]));
+        }
+        Format::Template(template) => {
+            return Err(format!("invalid format for --history: '{}'", template).into());
+        }
+    }
+    Ok(())
+}
+
+/// Whether `git revert` with `args` reverts a single commit and commits it,
+/// rather than a range or carrying on with an earlier revert.
+fn reverts_one_commit(args: &[&str]) -> bool {
+    let mut revisions = Vec::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        match arg {
+            "-n" | "--no-commit" | "--continue" | "--skip" | "--quit" | "--abort" => {
+                return false;
+            }
+            "-m" | "--mainline" | "-X" | "--strategy-option" | "--strategy" | "--cleanup" => {
+                args.next();
+            }
+            arg if !arg.starts_with('-') => revisions.push(arg),
+            _ => {}
+        }
+    }
+    matches!(revisions.as_slice(), [revision] if !revision.contains(".."))
+}
+
+/// Whether `git revert` with `args` has the message edited, which it only
+/// does on a terminal unless told otherwise.
+fn edits_revert_message(args: &[&str]) -> bool {
+    args.iter()
+        .rev()
+        .find_map(|&arg| match arg {
+            "-e" | "--edit" => Some(true),
+            "--no-edit" => Some(false),
+            _ => None,
+        })
+        .unwrap_or_else(|| io::stdin().is_terminal())
+}
+
+/// Opens config for writing only for commands that need signing off, which
+/// `peek` tells apart without writing anything.
+fn signoff_together(
+    command: &str,
+    peek: &GitTogether<git::Config>,
+) -> Result<Option<GitTogether<git::Config>>> {
+    if !peek.is_signoff_cmd(command) {
+        return Ok(None);
+    }
+
+    let gt = GitTogether::new(default_scope())?;
+    if !gt.is_signoff_cmd(command) || gt.is_paused() || !gt.check_active()? {
+        return Ok(None);
+    }
+    Ok(Some(gt))
+}
+
+/// Where the pair goes without `--global`, `--local` or `--file`: the
+/// current worktree's own config once the repo has linked worktrees, so that
+/// each of them can have its own pair.
+fn default_scope() -> ConfigScope {
+    match git::Repo::new() {
+        Ok(repo) if repo.uses_worktrees() => ConfigScope::Worktree,
+        _ => ConfigScope::Local,
+    }
+}
+
+/// The scope the active pair comes from, so that it's paused, resumed or
+/// cleared there rather than in the default scope.
+fn active_pair_scope() -> Result<ConfigScope> {
+    let gt = GitTogether::new(default_scope())?;
+    Ok(match gt.active_scope().as_deref() {
+        Some("global") => ConfigScope::Global,
+        Some("local") => ConfigScope::Local,
+        Some("worktree") => ConfigScope::Worktree,
+        _ => default_scope(),
+    })
+}
+
+/// Replaces this process with `cmd` so that git gets the terminal and
+/// signals to itself.
+#[cfg(unix)]
+fn exec(cmd: &mut Command) -> Result<i32> {
+    use std::os::unix::process::CommandExt;
+
+    Err(cmd.exec()).chain_err(|| "failed to execute process")
+}
+
+#[cfg(not(unix))]
+fn exec(cmd: &mut Command) -> Result<i32> {
+    let status = cmd.status().chain_err(|| "failed to execute process")?;
+    Ok(exit_code(status))
+}
+
+/// Runs `cmd` to completion while ignoring `SIGINT` and `SIGQUIT`, like git
+/// does for the commands it runs, so that `^C` stops the child and not us.
+#[cfg(unix)]
+fn wait_for(cmd: &mut Command) -> Result<ExitStatus> {
+    let mut child = cmd.spawn().chain_err(|| "failed to execute process")?;
+
+    // SAFETY: no other threads are changing signal handlers
+    let (int, quit) = unsafe {
+        (
+            libc::signal(libc::SIGINT, libc::SIG_IGN),
+            libc::signal(libc::SIGQUIT, libc::SIG_IGN),
+        )
+    };
+    let status = child.wait();
+    unsafe {
+        libc::signal(libc::SIGINT, int);
+        libc::signal(libc::SIGQUIT, quit);
+    }
+
+    status.chain_err(|| "failed to execute process")
+}
 
-    args.retain(|&arg| arg != "--global");
+#[cfg(not(unix))]
+fn wait_for(cmd: &mut Command) -> Result<ExitStatus> {
+    cmd.status().chain_err(|| "failed to execute process")
+}
+
+/// Exits like git does, with 128 plus the signal if the process was killed.
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(128)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(128)
+}
+
+pub fn run() -> Result<i32> {
+    // Guards against git-together finding itself instead of git
+    let depth = env::var("GIT_TOGETHER_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse::<u32>().ok())
+        .unwrap_or(0);
+    if depth >= MAX_DEPTH {
+        return Err(format!(
+            "git-together called itself {} times in a row; set GIT_TOGETHER_GIT or \
+             git-together.gitPath to the real git",
+            depth
+        )
+        .into());
+    }
+    env::set_var("GIT_TOGETHER_DEPTH", (depth + 1).to_string());
+
+    let all_args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<&str> = all_args.iter().map(String::as_ref).collect();
 
     let mut skip_next = false;
     let command = args
@@ -60,28 +583,222 @@ pub fn run() -> Result<i32> {
     let global_args = split_args.next().unwrap_or(&[]);
     let command_args = split_args.next().unwrap_or(&[]);
 
+    // git resolves `-C` itself, so it needs to start where we did
+    let original_dir = env::current_dir().ok();
+    apply_location(global_args)?;
+
     let code = if TRIGGERS.contains(command) {
-        match command_args {
+        // Authors are shared by every worktree
+        let is_roster = command_args
+            .iter()
+            .any(|arg| ["--add", "--edit", "--remove", "--rename"].contains(arg));
+        // `.git-together` is shared, so the pair stays out of it
+        if command_args.contains(&"--file") && !is_roster {
+            return Err("--file only works with --add, --edit, --remove and --rename".into());
+        }
+        let scope = if command_args.contains(&"--global") {
+            ConfigScope::Global
+        } else if command_args.contains(&"--file") {
+            ConfigScope::File
+        } else if command_args.contains(&"--worktree") {
+            ConfigScope::Worktree
+        } else if command_args.contains(&"--local") || is_roster {
+            ConfigScope::Local
+        } else if command_args
+            .iter()
+            .any(|arg| ["--clear", "--pause", "--resume"].contains(arg))
+        {
+            active_pair_scope()?
+        } else {
+            default_scope()
+        };
+
+        let mut gt = GitTogether::new(scope)?;
+        let force = command_args.contains(&"--force");
+        let keep_trailers = command_args.contains(&"--keep-trailers");
+        let command_args: Vec<_> = command_args
+            .iter()
+            .filter(|arg| {
+                ![
+                    "--force",
+                    "--global",
+                    "--file",
+                    "--local",
+                    "--worktree",
+                    "--keep-trailers",
+                ]
+                .contains(arg)
+            })
+            .cloned()
+            .collect();
+        let (format_arg, command_args) = take_option(&command_args, "--format")?;
+        // Only author listings take these, stairs has its own formats
+        let format = match command_args.as_slice() {
+            ["stairs", ..] => Format::Text,
+            _ => Format::new(format_arg)?,
+        };
+        let (trailers, command_args) = take_options(&command_args, "--trailer")?;
+        let (joining, leaving, command_args) = take_membership(&command_args)?;
+
+        match command_args.as_slice() {
+            [] if !joining.is_empty() || !leaving.is_empty() => {
+                let inits = gt.change_active(&joining, &leaving)?;
+                let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+                let authors = gt.set_members(&inits)?;
+                gt.set_trailers(&trailers, true)?;
+
+                let authors: Vec<_> = inits.iter().cloned().zip(authors.iter()).collect();
+                print_authors(&gt, format, &authors, true, |_, author| author.to_string())?;
+            }
+            [arg, ..] if !joining.is_empty() || !leaving.is_empty() => {
+                return Err(format!("unexpected argument: '{}'", arg).into());
+            }
             [] => {
                 let inits = gt.get_active()?;
                 let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
                 let authors = gt.get_authors(&inits)?;
-
-                for (initials, author) in inits.iter().zip(authors.iter()) {
-                    println!("{}: {}", initials, author);
+                if !trailers.is_empty() {
+                    gt.set_trailers(&trailers, true)?;
                 }
+
+                let authors: Vec<_> = inits.iter().cloned().zip(authors.iter()).collect();
+                print_authors(&gt, format, &authors, true, |initials, author| {
+                    format!("{}: {}", initials, author)
+                })?;
             }
             ["--list"] => {
                 let authors = gt.all_authors()?;
-                let mut sorted: Vec<_> = authors.iter().collect();
+                let mut sorted: Vec<(&str, _)> =
+                    authors.iter().map(|(i, a)| (i.as_ref(), a)).collect();
                 sorted.sort_by(|a, b| a.0.cmp(b.0));
 
-                for (initials, author) in sorted {
-                    println!("{}: {}", initials, author);
+                print_authors(&gt, format, &sorted, false, |initials, author| {
+                    format!("{}: {}", initials, author)
+                })?;
+            }
+            ["-"] => {
+                let previous = gt.previous_active()?;
+                let inits: Vec<_> = previous.iter().map(String::as_ref).collect();
+                let authors = gt.set_active(&inits)?;
+                gt.set_trailers(&trailers, keep_trailers)?;
+
+                let authors: Vec<_> = inits.iter().cloned().zip(authors.iter()).collect();
+                print_authors(&gt, format, &authors, true, |_, author| author.to_string())?;
+            }
+            ["--history"] => {
+                print_history(&gt.history()?, format, time::Timestamp::now()?)?;
+            }
+            ["--pause"] => {
+                gt.pause()?;
+            }
+            ["--resume"] => {
+                for author in gt.resume()? {
+                    println!("{}", author);
                 }
             }
             ["--clear"] => {
                 gt.clear_active()?;
+                if !keep_trailers {
+                    gt.set_trailers(&[], false)?;
+                }
+            }
+            ["--add", initials, raw] => {
+                let author = gt.add_author(initials, raw, force)?;
+                println!("{}: {}", initials, author);
+            }
+            ["--edit", initials, raw] => {
+                let author = gt.edit_author(initials, raw)?;
+                println!("{}: {}", initials, author);
+            }
+            ["--remove", initials] => {
+                gt.remove_author(initials)?;
+            }
+            ["--rename", old, new] => {
+                let author = gt.rename_author(old, new, force)?;
+                println!("{}: {}", new, author);
+            }
+            ["stats", rest @ ..] => {
+                let (range, since) = log_args(rest)?;
+                let commits = git::Repo::new()?.commits(range, since)?;
+                let tally = history::Tally::new(&commits, &gt.roster()?);
+
+                println!("Pairs:");
+                for (pair, count) in by_count(&tally.pairs) {
+                    println!("  {}: {}", pair.join("+"), count);
+                }
+                println!("People:");
+                for (initials, count) in by_count(&tally.people) {
+                    println!("  {}: {}", initials, count);
+                }
+            }
+            ["stairs", rest @ ..] => {
+                let stairs_format = StairsFormat::new(format_arg)?;
+                let (range, since) = log_args(rest)?;
+                let commits = git::Repo::new()?.commits(range, since)?;
+                let stairs = history::Stairs::new(&commits, &gt.roster()?);
+
+                let output = match stairs_format {
+                    StairsFormat::Text => stairs.to_text(),
+                    StairsFormat::Markdown => stairs.to_markdown(),
+                    StairsFormat::Csv => stairs.to_csv(),
+                };
+                print!("{}", output);
+            }
+            ["suggest", rest @ ..] => {
+                let (since, rest) = take_option(rest, "--since")?;
+                let set = rest.contains(&"--set");
+                let present: Vec<_> = rest.into_iter().filter(|&arg| arg != "--set").collect();
+                let present = gt.expand_groups(&present)?;
+                let mut present: Vec<_> = present.iter().map(String::as_ref).collect();
+
+                let roster = gt.roster()?;
+                let authors = gt.all_authors()?;
+                let mut everyone: Vec<_> = authors
+                    .iter()
+                    .filter(|(_, author)| author.is_active())
+                    .map(|(initials, _)| initials.as_str())
+                    .collect();
+                if present.is_empty() {
+                    everyone.sort();
+                    present = everyone;
+                } else {
+                    gt.get_authors(&present)?;
+                }
+
+                let since = since
+                    .map(|raw| time::parse_since(raw, time::Timestamp::now()?))
+                    .transpose()?;
+                let commits = git::Repo::new()?.commits(None, since)?;
+                let pairs = history::suggest(&commits, &roster, &present);
+                for pair in &pairs {
+                    println!("{}", pair.join("+"));
+                }
+
+                if set {
+                    let caller = gt.caller()?;
+                    let mut pair: Vec<_> = pairs
+                        .iter()
+                        .find(|pair| pair.contains(&caller))
+                        .ok_or_else(|| format!("'{}' isn't one of the people present", caller))?
+                        .iter()
+                        .map(String::as_ref)
+                        .collect();
+                    pair.sort_by_key(|&initials| initials != caller);
+
+                    let authors = gt.set_active(&pair)?;
+                    gt.set_trailers(&trailers, keep_trailers)?;
+                    for author in authors {
+                        println!("{}", author);
+                    }
+                }
+            }
+            ["install-hooks"] => {
+                let dir = git::Repo::new()?.hooks_dir()?;
+                hooks::install(&dir, force)?;
+                println!("installed hooks in {}", dir.display());
+            }
+            ["hook", name, hook_args @ ..] => {
+                run_hook(&mut gt, name, hook_args)?;
             }
             ["--version"] => {
                 println!(
@@ -91,36 +808,95 @@ pub fn run() -> Result<i32> {
                 );
             }
             _ => {
-                let authors = gt.set_active(command_args)?;
-                for author in authors {
-                    println!("{}", author);
-                }
+                let inits = gt.expand_groups(&command_args)?;
+                let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+                let authors = gt.set_active(&inits)?;
+                gt.set_trailers(&trailers, keep_trailers)?;
+
+                let authors: Vec<_> = inits.iter().cloned().zip(authors.iter()).collect();
+                print_authors(&gt, format, &authors, true, |_, author| author.to_string())?;
             }
         }
 
         0
-    } else if gt.is_signoff_cmd(command) {
-        if command == &"merge" {
-            env::set_var("GIT_TOGETHER_NO_SIGNOFF", "1");
-        }
+    } else {
+        // Everything else has to leave config alone unless it needs signing
+        // off, so only a read-only look tells which
+        let peek = GitTogether::read_only()?;
+        match signoff_together(command, &peek)? {
+            Some(mut gt) => {
+                let alias = gt.expand_alias(command);
+                let builtin = alias.first().map_or("", String::as_str);
+                let mut revert_args: Vec<_> = alias.iter().skip(1).map(String::as_ref).collect();
+                revert_args.extend(command_args);
+                // `git revert` doesn't accept `--trailer`, so reverts that
+                // need trailers leave the changes for `git commit` instead
+                let commit_revert = builtin == "revert"
+                    && reverts_one_commit(&revert_args)
+                    && !gt.commit_trailers()?.is_empty()
+                    && !git::Repo::new()?.has_staged_changes()?;
+                if builtin == "merge" {
+                    env::set_var("GIT_TOGETHER_NO_SIGNOFF", "1");
+                }
+                // Only `git commit` accepts `--trailer`
+                if builtin != "commit" && !commit_revert {
+                    env::set_var("GIT_TOGETHER_NO_TRAILERS", "1");
+                    let dropped = gt.commit_trailers()?;
+                    if !dropped.is_empty() {
+                        eprintln!(
+                            "warning: `git {}` doesn't take trailers, so these are left out:",
+                            builtin
+                        );
+                        for trailer in dropped {
+                            eprintln!("  {}", trailer);
+                        }
+                    }
+                }
 
-        let mut cmd = Command::new("git");
-        let cmd = cmd.args(global_args);
-        let cmd = cmd.arg(command);
-        let cmd = gt.signoff(cmd)?;
-        let cmd = cmd.args(command_args);
+                let binary = git::binary(gt.git_path())?;
+                let git = || {
+                    let mut cmd = Command::new(&binary);
+                    if let Some(dir) = &original_dir {
+                        cmd.current_dir(dir);
+                    }
+                    cmd.args(global_args);
+                    cmd
+                };
 
-        let status = cmd.status().chain_err(|| "failed to execute process")?;
-        if status.success() {
-            gt.rotate_active()?;
+                let status = if commit_revert {
+                    let mut commit = git();
+                    commit.arg("commit");
+                    if !edits_revert_message(&revert_args) {
+                        commit.arg("--no-edit");
+                    }
+                    let commit = gt.signoff(&mut commit)?.env("GIT_TOGETHER_SIGNOFF", "1");
+
+                    let mut revert = git();
+                    let status =
+                        wait_for(revert.arg(command).arg("--no-commit").args(command_args))?;
+                    if status.success() {
+                        wait_for(commit)?
+                    } else {
+                        status
+                    }
+                } else {
+                    let mut cmd = git();
+                    let cmd = gt.signoff(cmd.arg(command))?;
+                    wait_for(cmd.args(command_args).env("GIT_TOGETHER_SIGNOFF", "1"))?
+                };
+                if status.success() {
+                    rotate(&mut gt)?;
+                }
+                exit_code(status)
+            }
+            None => {
+                let mut cmd = Command::new(git::binary(peek.git_path())?);
+                if let Some(dir) = &original_dir {
+                    cmd.current_dir(dir);
+                }
+                exec(cmd.args(args))?
+            }
         }
-        status.code().ok_or("process terminated by signal")?
-    } else {
-        let status = Command::new("git")
-            .args(args)
-            .status()
-            .chain_err(|| "failed to execute process")?;
-        status.code().ok_or("process terminated by signal")?
     };
 
     Ok(code)
@@ -129,15 +905,157 @@ pub fn run() -> Result<i32> {
 pub struct GitTogether<C> {
     config: C,
     author_parser: AuthorParser,
+    /// The current branch, when pairs are per branch
+    branch: Option<String>,
 }
 
 pub enum ConfigScope {
     Local,
     Global,
+    /// The repo's `.git-together` file
+    File,
+    /// The current worktree's `config.worktree`
+    Worktree,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Attribution {
+    Signoff,
+    Coauthors,
+}
+
+/// What to do when committing without an active pair
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Policy {
+    Strict,
+    Warn,
+    Off,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rotation {
+    None,
+    RoundRobin,
+    DriverOnly,
+    LeastRecent,
+}
+
+impl Rotation {
+    /// Rotates the active list in place, where `authored[i]` is how many
+    /// recent commits were authored by `inits[i]`.
+    fn rotate<T>(self, inits: &mut [T], authored: &[usize]) {
+        let len = inits.len();
+        match self {
+            Rotation::None => {}
+            Rotation::RoundRobin if len > 1 => inits.rotate_left(1),
+            Rotation::DriverOnly if len > 2 => {
+                // Park the committer up front while the others rotate
+                inits.swap(0, 1);
+                inits[1..].rotate_left(1);
+                inits.swap(0, 1);
+            }
+            Rotation::LeastRecent if len > 1 => {
+                let next = (1..=len)
+                    .map(|i| i % len)
+                    .min_by_key(|&i| authored[i])
+                    .unwrap_or(0);
+                inits.rotate_left(next);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Why the active authors changed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cause {
+    Manual,
+    Rotation,
+    Clear,
+}
+
+impl Cause {
+    fn as_str(self) -> &'static str {
+        match self {
+            Cause::Manual => "manual",
+            Cause::Rotation => "rotation",
+            Cause::Clear => "clear",
+        }
+    }
+}
+
+/// A change to the active authors, kept in `git-together.history` as
+/// `<seconds> <cause> <previous> <active>`, with `-` for nobody.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub time: i64,
+    pub cause: Cause,
+    pub previous: Vec<String>,
+    pub active: Vec<String>,
+}
+
+impl Change {
+    fn parse(raw: &str) -> Result<Change> {
+        let invalid = || format!("invalid history entry: '{}'", raw);
+        let parse_inits = |inits: &str| match inits {
+            "-" => Vec::new(),
+            inits => inits.split('+').map(String::from).collect(),
+        };
+
+        match raw.split(' ').collect::<Vec<_>>().as_slice() {
+            [time, cause, previous, active] => {
+                let cause = match *cause {
+                    "manual" => Cause::Manual,
+                    "rotation" => Cause::Rotation,
+                    "clear" => Cause::Clear,
+                    _ => return Err(invalid().into()),
+                };
+                Ok(Change {
+                    time: time.parse().chain_err(invalid)?,
+                    cause,
+                    previous: parse_inits(previous),
+                    active: parse_inits(active),
+                })
+            }
+            _ => Err(invalid().into()),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inits = |inits: &[String]| match inits {
+            [] => "-".to_string(),
+            inits => inits.join("+"),
+        };
+        write!(
+            f,
+            "{} {} {} {}",
+            self.time,
+            self.cause.as_str(),
+            inits(&self.previous),
+            inits(&self.active)
+        )
+    }
 }
 
 impl GitTogether<git::Config> {
+    /// Reads the repo's config, or just the user's outside a repo, without
+    /// including `.git-together` or writing anything else.
+    pub fn read_only() -> Result<Self> {
+        let config = git::Repo::new()
+            .and_then(|repo| repo.config())
+            .or_else(|_| git::Config::new(ConfigScope::Local))?;
+        Ok(GitTogether {
+            config,
+            author_parser: AuthorParser { domain: None },
+            branch: None,
+        })
+    }
+
     pub fn new(scope: ConfigScope) -> Result<Self> {
+        // Branches only make sense for a repo's own pair
+        let local = matches!(scope, ConfigScope::Local | ConfigScope::Worktree);
         let config = match scope {
             ConfigScope::Local => {
                 let repo = git::Repo::new();
@@ -148,56 +1066,513 @@ impl GitTogether<git::Config> {
                 repo.and_then(|r| r.config())
                     .or_else(|_| git::Config::new(scope))?
             }
+            ConfigScope::Worktree => {
+                let repo = git::Repo::new()?;
+                let _ = repo.auto_include(&format!(".{}", NAMESPACE));
+                repo.worktree_config()?
+            }
             ConfigScope::Global => git::Config::new(scope)?,
+            ConfigScope::File => {
+                let repo = git::Repo::new()?;
+                let filename = format!(".{}", NAMESPACE);
+                let config = repo.file_config(&filename)?;
+                repo.auto_include(&filename)?;
+                config
+            }
         };
 
-        let domain = config.get(&namespaced("domain")).ok();
+        let domain = match config.get(&namespaced("domain")) {
+            Ok(_) => domain(&config)?,
+            Err(_) => match git::Repo::new().and_then(|repo| repo.config()) {
+                Ok(config) => domain(&config)?,
+                Err(_) => None,
+            },
+        };
         let author_parser = AuthorParser { domain };
 
+        let per_branch = local
+            && config
+                .get(&namespaced("perBranch"))
+                .is_ok_and(|per_branch| per_branch == "true");
+        let branch = if per_branch {
+            git::Repo::new().ok().and_then(|repo| repo.branch())
+        } else {
+            None
+        };
+
         Ok(GitTogether {
             config,
             author_parser,
+            branch,
         })
     }
 }
 
 impl<C: config::Config> GitTogether<C> {
+    /// Sets the active authors and starts a new session.
     pub fn set_active(&mut self, inits: &[&str]) -> Result<Vec<Author>> {
-        let authors = self.get_authors(inits)?;
-        self.config.set(&namespaced("active"), &inits.join("+"))?;
+        self.activate(inits, Cause::Manual, true)
+    }
+
+    /// Sets the active authors after people join or leave, which carries on
+    /// the current session rather than starting a new one.
+    pub fn set_members(&mut self, inits: &[&str]) -> Result<Vec<Author>> {
+        self.activate(inits, Cause::Manual, false)
+    }
+
+    /// Writes the active authors, where starting a new session goes to the
+    /// current branch in per-branch mode but anything else sticks to
+    /// whichever pair is active.
+    fn activate(&mut self, inits: &[&str], cause: Cause, restart: bool) -> Result<Vec<Author>> {
+        let inits = self.expand_groups(inits)?;
+        let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+        let authors = self.get_authors(&inits)?;
+        if self.is_paused() {
+            self.config.clear(&namespaced("paused"))?;
+        }
 
-        self.save_original_user()?;
-        if let Some(author) = authors.get(0) {
+        let key = match self.branch {
+            Some(ref branch) if restart => branch_key(branch),
+            _ => self.active_key(),
+        };
+        let previous = self.active_scope();
+        let before = self.get_active().unwrap_or_default();
+        self.config.set(&key, &inits.join("+"))?;
+
+        // Only the first pair in a scope replaces the original identity
+        let scope = self.config.scope(&key)?;
+        if previous.as_ref() != Some(&scope) {
+            self.save_original_user(&scope)?;
+        }
+        if let Some(author) = authors.first() {
             self.set_user(&author.name, &author.email)?;
         }
+        if restart {
+            self.restart_session()?;
+        }
 
+        self.record(cause, before, inits.iter().map(|&i| i.into()).collect())?;
         Ok(authors)
     }
 
-    pub fn clear_active(&mut self) -> Result<()> {
-        self.config.clear(&namespaced("active"))?;
+    /// Adds a change to the history, keeping only the most recent
+    /// `HISTORY_LIMIT` rotations and as many other changes, so that a long
+    /// session's rotations don't crowd out the pairs before it.
+    fn record(&mut self, cause: Cause, previous: Vec<String>, active: Vec<String>) -> Result<()> {
+        if previous == active {
+            return Ok(());
+        }
+
+        let change = Change {
+            time: time::Timestamp::now()?.seconds,
+            cause,
+            previous,
+            active,
+        };
+        let key = namespaced("history");
+        let mut changes = self.history()?;
+        changes.push(change);
 
-        let _ = self.config.clear("user.name");
-        let _ = self.config.clear("user.email");
+        let (mut rotations, mut others) = (0, 0);
+        let mut kept: Vec<_> = changes
+            .iter()
+            .rev()
+            .filter(|change| {
+                let count = if change.cause == Cause::Rotation {
+                    &mut rotations
+                } else {
+                    &mut others
+                };
+                *count += 1;
+                *count <= HISTORY_LIMIT
+            })
+            .collect();
+        // Nothing to drop, so the new change just goes on the end
+        if kept.len() == changes.len() {
+            return self.config.add(&key, &kept[0].to_string());
+        }
+        kept.reverse();
 
+        self.config.clear_all(&key)?;
+        for change in kept {
+            self.config.add(&key, &change.to_string())?;
+        }
         Ok(())
     }
 
-    fn save_original_user(&mut self) -> Result<()> {
-        if let Ok(name) = self.config.get("user.name") {
-            let key = namespaced("user.name");
-            self.config
-                .get(&key)
-                .map(|_| ())
-                .or_else(|_| self.config.set(&key, &name))?;
+    /// Changes to the active authors, oldest first
+    pub fn history(&self) -> Result<Vec<Change>> {
+        self.config
+            .get_multi(&namespaced("history"))?
+            .iter()
+            .map(|raw| Change::parse(raw))
+            .collect()
+    }
+
+    /// The active authors from before the last change, like `cd -`, not
+    /// counting rotations.
+    pub fn previous_active(&self) -> Result<Vec<String>> {
+        self.history()?
+            .into_iter()
+            .rfind(|change| change.cause != Cause::Rotation)
+            .map(|change| change.previous)
+            .filter(|previous| !previous.is_empty())
+            .ok_or_else(|| "no previous pair to go back to".into())
+    }
+
+    fn restart_session(&mut self) -> Result<()> {
+        let now = time::Timestamp::now()?;
+        self.config
+            .set(&namespaced("started"), &now.seconds.to_string())
+    }
+
+    /// When the active pair was set, in seconds since the epoch
+    pub fn started(&self) -> Option<i64> {
+        let key = namespaced("started");
+        if self.config.scope(&key).ok() != self.active_scope() {
+            return None;
+        }
+        self.config.get(&key).ok()?.parse().ok()
+    }
+
+    /// Warns when the active pair was set longer than
+    /// `git-together.warnAfter` ago, and refuses to go on once it's past
+    /// `git-together.expireAfter`.
+    pub fn check_session(&self, now: time::Timestamp) -> Result<()> {
+        let started = match self.started() {
+            Some(started) => started,
+            None => return Ok(()),
+        };
+        let age = now.seconds - started;
+
+        if let Ok(raw) = self.config.get(&namespaced("expireAfter")) {
+            let expired = match raw.as_str() {
+                "end-of-day" => {
+                    let started = time::Timestamp {
+                        seconds: started,
+                        offset: now.offset,
+                    };
+                    now.local_days() != started.local_days()
+                }
+                _ => {
+                    age > time::parse_duration(&raw)
+                        .chain_err(|| format!("invalid git-together.expireAfter: '{}'", raw))?
+                }
+            };
+            if expired {
+                return Err(format!(
+                    "the pair was set {} ago and has expired (run `git with` to start a new session)",
+                    time::format_duration(age)
+                )
+                .into());
+            }
+        }
+
+        let warn_after = match self.config.get(&namespaced("warnAfter")) {
+            Ok(raw) => time::parse_duration(&raw)
+                .chain_err(|| format!("invalid git-together.warnAfter: '{}'", raw))?,
+            Err(_) => WARN_AFTER,
+        };
+        if age > warn_after {
+            eprintln!(
+                "warning: the pair was set {} ago (run `git with` to start a new session)",
+                time::format_duration(age)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The active authors after `joining` are added to the end and `leaving`
+    /// are taken out, keeping everyone else's place in the rotation.
+    pub fn change_active(&self, joining: &[&str], leaving: &[&str]) -> Result<Vec<String>> {
+        let mut inits = self.get_active()?;
+
+        for initials in self.expand_groups(leaving)? {
+            let i = inits
+                .iter()
+                .position(|active| *active == initials)
+                .ok_or_else(|| format!("'{}' isn't in the current pair", initials))?;
+            inits.remove(i);
+        }
+
+        for initials in self.expand_groups(joining)? {
+            if inits.contains(&initials) {
+                return Err(format!("'{}' is already in the current pair", initials).into());
+            }
+            self.get_author(&initials)?;
+            inits.push(initials);
+        }
+
+        if inits.is_empty() {
+            return Err("can't leave without anyone left (use --clear instead)".into());
+        }
+        Ok(inits)
+    }
+
+    /// Clears the active authors and restores the identity from before
+    /// pairing, if there was one in the same scope.
+    pub fn clear_active(&mut self) -> Result<()> {
+        self.check_active_writable()?;
+        let paused = self.is_paused();
+        let started = self.started().is_some();
+        let before = self.get_active().unwrap_or_default();
+
+        let key = self.active_key();
+        let scope = self.config.scope(&key).ok();
+        self.config.clear(&key)?;
+        self.record(Cause::Clear, before, self.get_active().unwrap_or_default())?;
+
+        // Clearing a branch's pair falls back to the repo-wide one
+        if let Ok(inits) = self.get_active() {
+            let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+            let authors = self.get_authors(&inits)?;
+            if let (Some(author), false) = (authors.first(), paused) {
+                self.set_user(&author.name, &author.email)?;
+            }
+            return Ok(());
+        }
+
+        if paused {
+            self.config.clear(&namespaced("paused"))?;
+        }
+        if started {
+            self.config.clear(&namespaced("started"))?;
+        }
+        self.restore_original_user(scope, true)
+    }
+
+    /// Goes back to the identity from before pairing without forgetting the
+    /// active authors or where they are in the rotation.
+    pub fn pause(&mut self) -> Result<()> {
+        self.check_active_writable()?;
+        if self.is_paused() {
+            return Err("already paused".into());
+        }
+
+        self.config.set(&namespaced("paused"), "true")?;
+        self.restore_original_user(self.active_scope(), false)
+    }
+
+    pub fn resume(&mut self) -> Result<Vec<Author>> {
+        self.check_active_writable()?;
+        if !self.is_paused() {
+            return Err("not paused".into());
+        }
+        self.config.clear(&namespaced("paused"))?;
+
+        let inits = self.get_active()?;
+        let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+        let authors = self.get_authors(&inits)?;
+        if let Some(author) = authors.first() {
+            self.set_user(&author.name, &author.email)?;
+        }
+        Ok(authors)
+    }
+
+    /// Fails unless the active pair is set in the config being changed, since
+    /// anything written elsewhere would only shadow it.
+    fn check_active_writable(&self) -> Result<()> {
+        let key = self.active_key();
+        self.config.get(&key)?;
+        if self.config.writable(&key) {
+            return Ok(());
+        }
+        Err(format!(
+            "the pair is set in {} (use --global or --local to change it there)",
+            self.config.origin(&key)?
+        )
+        .into())
+    }
+
+    /// Whether the active pair is paused, which only counts when paused in
+    /// the same scope as the pair.
+    pub fn is_paused(&self) -> bool {
+        let key = namespaced("paused");
+        self.config.get(&key).is_ok_and(|paused| paused == "true")
+            && self.config.scope(&key).ok() == self.active_scope()
+    }
+
+    /// Puts back the identity saved in `scope` when pairing started, and
+    /// forgets it if `forget` is set.
+    fn restore_original_user(&mut self, scope: Option<String>, forget: bool) -> Result<()> {
+        for name in ["user.name", "user.email"] {
+            let _ = self.config.clear(name);
+
+            let saved_key = namespaced(name);
+            if self.config.scope(&saved_key).ok() != scope {
+                continue;
+            }
+
+            let saved = self.config.get(&saved_key)?;
+            // Don't pin a value that's already inherited from another scope
+            if self.config.get(name).ok().as_ref() != Some(&saved) {
+                self.config.set(name, &saved)?;
+            }
+            if forget {
+                self.config.clear(&saved_key)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_author(&mut self, initials: &str, raw: &str, force: bool) -> Result<Author> {
+        validate_initials(initials)?;
+        let author = self.parse_author(initials, raw)?;
+
+        if self.get_author(initials).is_ok() {
+            if !force {
+                return Err(format!(
+                    "author already exists for '{}' (use --force to replace it)",
+                    initials
+                )
+                .into());
+            }
+            // Authors from other scopes are just overridden
+            self.check_shadowable(initials)?;
+            for (key, _) in self.author_keys(initials)? {
+                self.config.clear(&key)?;
+            }
+        }
+
+        self.config.set(&author_key(initials), raw)?;
+        Ok(author)
+    }
+
+    /// Replaces the name and email of an author, keeping any other fields of
+    /// the structured form.
+    pub fn edit_author(&mut self, initials: &str, raw: &str) -> Result<Author> {
+        let previous = self.get_author(initials)?;
+        let author = self.parse_author(initials, raw)?;
+        self.check_shadowable(initials)?;
+        if self.author_fields(initials)?.is_empty() {
+            self.config.set(&author_key(initials), raw)?;
+        } else {
+            self.config
+                .set(&author_field_key(initials, "name"), &author.name)?;
+            self.config
+                .set(&author_field_key(initials, "email"), &author.email)?;
+        }
+
+        Ok(Author {
+            fields: previous.fields,
+            ..author
+        })
+    }
+
+    pub fn remove_author(&mut self, initials: &str) -> Result<()> {
+        for (key, _) in self.removable_author_keys(initials)? {
+            self.config.clear(&key)?;
+        }
+        Ok(())
+    }
+
+    pub fn rename_author(&mut self, old: &str, new: &str, force: bool) -> Result<Author> {
+        validate_initials(new)?;
+        let author = self.get_author(old)?;
+        let keys = self.removable_author_keys(old)?;
+
+        if self.get_author(new).is_ok() {
+            if !force {
+                return Err(format!(
+                    "author already exists for '{}' (use --force to replace it)",
+                    new
+                )
+                .into());
+            }
+            self.check_shadowable(new)?;
+            for (key, _) in self.author_keys(new)? {
+                self.config.clear(&key)?;
+            }
+        }
+
+        for (key, field) in &keys {
+            let value = self.config.get(key)?;
+            let new_key = match field {
+                Some(field) => author_field_key(new, field),
+                None => author_key(new),
+            };
+            self.config.set(&new_key, &value)?;
+        }
+        for (key, _) in &keys {
+            self.config.clear(key)?;
+        }
+        Ok(author)
+    }
+
+    /// The keys for `initials` in the config being changed, along with the
+    /// field each one holds in the structured form.
+    fn author_keys(&self, initials: &str) -> Result<Vec<(String, Option<String>)>> {
+        Ok(self
+            .all_author_keys(initials)?
+            .into_iter()
+            .filter(|(key, _)| self.config.writable(key))
+            .collect())
+    }
+
+    fn all_author_keys(&self, initials: &str) -> Result<Vec<(String, Option<String>)>> {
+        let mut keys = vec![(author_key(initials), None)];
+        for field in self.author_fields(initials)?.into_keys() {
+            keys.push((author_field_key(initials, &field), Some(field)));
+        }
+        Ok(keys)
+    }
+
+    /// Checks that `initials` can be written to the config being changed,
+    /// which isn't the case for authors included into it.
+    fn check_shadowable(&self, initials: &str) -> Result<()> {
+        let included = self
+            .all_author_keys(initials)?
+            .into_iter()
+            .any(|(key, _)| self.config.get(&key).is_ok() && !self.config.shadowable(&key));
+        if included {
+            return Err(self.author_elsewhere(initials));
+        }
+        Ok(())
+    }
+
+    fn author_elsewhere(&self, initials: &str) -> Error {
+        let mut key = author_field_key(initials, "name");
+        if self.config.get(&key).is_err() {
+            key = author_key(initials);
+        }
+        match self.config.origin(&key) {
+            Ok(origin) => format!(
+                "author for '{}' is set in {} (use --file or --global to change it there)",
+                initials, origin
+            )
+            .into(),
+            Err(err) => err,
         }
+    }
+
+    /// Like `author_keys`, but for an author that has to exist in the config
+    /// being changed.
+    fn removable_author_keys(&self, initials: &str) -> Result<Vec<(String, Option<String>)>> {
+        self.get_author(initials)?;
+        let keys = self.author_keys(initials)?;
+        if keys.is_empty() {
+            return Err(self.author_elsewhere(initials));
+        }
+        Ok(keys)
+    }
 
-        if let Ok(email) = self.config.get("user.email") {
-            let key = namespaced("user.email");
+    /// Saves `user.name` and `user.email` if they're set in `scope`, since
+    /// values inherited from other scopes come back on their own when the
+    /// pair is cleared.
+    fn save_original_user(&mut self, scope: &str) -> Result<()> {
+        for name in ["user.name", "user.email"] {
+            if self.config.scope(name).ok().as_deref() != Some(scope) {
+                continue;
+            }
+
+            let value = self.config.get(name)?;
+            let key = namespaced(name);
             self.config
                 .get(&key)
                 .map(|_| ())
-                .or_else(|_| self.config.set(&key, &email))?;
+                .or_else(|_| self.config.set(&key, &value))?;
         }
 
         Ok(())
@@ -214,18 +1589,94 @@ impl<C: config::Config> GitTogether<C> {
         let mut authors = HashMap::new();
         let raw = self.config.get_all(&namespaced("authors."))?;
         for (name, value) in raw {
-            let initials = name.split('.').last().ok_or("")?;
-            let author = self.parse_author(initials, &value)?;
+            let initials = name
+                .split('.')
+                .next_back()
+                .ok_or_else(|| format!("invalid author key: '{}'", name))?;
+            let author = self
+                .parse_author(initials, &value)
+                .chain_err(|| invalid_config(&self.config, &name))?;
             authors.insert(initials.into(), author);
         }
+
+        // The structured form wins over the string form
+        let prefix = namespaced("author.");
+        let mut structured: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+        for (name, value) in self.config.get_all(&prefix)? {
+            if let Some((initials, field)) = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.rsplit_once('.'))
+            {
+                structured
+                    .entry(initials.into())
+                    .or_default()
+                    .insert(field.into(), value);
+            }
+        }
+        for (initials, fields) in structured {
+            let author = self.parse_author_fields(&initials, fields)?;
+            authors.insert(initials, author);
+        }
         Ok(authors)
     }
 
+    /// Maps the lowercase email of every author to their initials.
+    pub fn roster(&self) -> Result<HashMap<String, String>> {
+        Ok(self
+            .all_authors()?
+            .into_iter()
+            .map(|(initials, author)| (author.email.to_lowercase(), initials))
+            .collect())
+    }
+
+    /// Initials of whoever is running git-together, going by the identity
+    /// they had before pairing.
+    pub fn caller(&self) -> Result<String> {
+        let email = self
+            .config
+            .get(&namespaced("user.email"))
+            .or_else(|_| self.config.get("user.email"))
+            .chain_err(|| "no user.email configured")?;
+        self.roster()?
+            .remove(&email.to_lowercase())
+            .ok_or_else(|| format!("no author found for '{}'", email).into())
+    }
+
     pub fn is_signoff_cmd(&self, cmd: &str) -> bool {
         let signoffs = ["commit", "merge", "revert"];
         signoffs.contains(&cmd) || self.is_signoff_alias(cmd)
     }
 
+    /// The git binary configured in `git-together.gitPath`, if any
+    pub fn git_path(&self) -> Option<String> {
+        self.config.get(&namespaced("gitPath")).ok()
+    }
+
+    /// The command and arguments that `cmd` runs, following aliases like git
+    /// does. Shell aliases and alias loops don't run a git command at all.
+    pub fn expand_alias(&self, cmd: &str) -> Vec<String> {
+        let mut words = vec![cmd.to_string()];
+        for _ in 0..MAX_DEPTH {
+            // Aliases can't shadow builtins
+            if ["commit", "merge", "revert"].contains(&words[0].as_str()) {
+                return words;
+            }
+            let alias = match self.config.get(&format!("alias.{}", words[0])) {
+                Ok(alias) if !alias.starts_with('!') => alias,
+                Ok(_) => return Vec::new(),
+                Err(_) => return words,
+            };
+
+            let mut expanded: Vec<_> = alias.split_whitespace().map(String::from).collect();
+            if expanded.is_empty() {
+                return Vec::new();
+            }
+            expanded.extend(words.drain(1..));
+            words = expanded;
+        }
+        Vec::new()
+    }
+
     fn is_signoff_alias(&self, cmd: &str) -> bool {
         self.config
             .get(&namespaced("aliases"))
@@ -236,16 +1687,20 @@ impl<C: config::Config> GitTogether<C> {
     }
 
     pub fn signoff<'a>(&self, cmd: &'a mut Command) -> Result<&'a mut Command> {
-        let active = self.config.get(&namespaced("active"))?;
-        let inits: Vec<_> = active.split('+').collect();
+        self.check_session(time::Timestamp::now()?)?;
+
+        let active = self.get_active().chain_err(|| NO_ACTIVE_PAIR)?;
+        let inits: Vec<_> = active.iter().map(String::as_ref).collect();
         let authors = self.get_authors(&inits)?;
 
-        let (author, committer) = match *authors.as_slice() {
-            [] => {
-                return Err("".into());
+        let attribution = self.attribution()?;
+        let (author, committer) = match (attribution, authors.as_slice()) {
+            (_, []) => {
+                return Err(NO_ACTIVE_PAIR.into());
             }
-            [ref solo] => (solo, solo),
-            [ref author, ref committer, ..] => (author, committer),
+            (Attribution::Coauthors, [ref author, ..]) => (author, author),
+            (Attribution::Signoff, [ref solo]) => (solo, solo),
+            (Attribution::Signoff, [ref author, ref committer, ..]) => (author, committer),
         };
 
         let cmd = cmd
@@ -255,69 +1710,1419 @@ impl<C: config::Config> GitTogether<C> {
             .env("GIT_COMMITTER_EMAIL", committer.email.clone());
 
         let no_signoff = env::var("GIT_TOGETHER_NO_SIGNOFF").is_ok();
-        Ok(if !no_signoff && author != committer {
+        if no_signoff {
+            return Ok(cmd);
+        }
+
+        let cmd = if attribution == Attribution::Signoff && author != committer {
             cmd.arg("--signoff")
         } else {
             cmd
+        };
+
+        if env::var("GIT_TOGETHER_NO_TRAILERS").is_ok() {
+            return Ok(cmd);
+        }
+
+        Ok(self
+            .commit_trailers()?
+            .iter()
+            .fold(cmd, |cmd, trailer| cmd.arg("--trailer").arg(trailer)))
+    }
+
+    /// Trailers for commits that git-together runs itself, where the
+    /// author and committer already credit the pair.
+    pub fn commit_trailers(&self) -> Result<Vec<String>> {
+        let active = self.get_active()?;
+        let inits: Vec<_> = active.iter().map(String::as_ref).collect();
+        let authors = self.get_authors(&inits)?;
+        let attribution = self.attribution()?;
+        let mut trailers = match attribution {
+            Attribution::Signoff => Vec::new(),
+            Attribution::Coauthors => trailers(attribution, &authors),
+        };
+        trailers.extend(self.session_trailers()?);
+        Ok(trailers)
+    }
+
+    /// Trailers crediting the active pair for commits that git-together
+    /// doesn't run itself. `author` is whoever git says wrote the commit,
+    /// which isn't the pair's current driver when amending after a rotation,
+    /// so the rest of the pair is counted on from them.
+    pub fn hook_trailers(&self, author: &Author) -> Result<Vec<String>> {
+        let active = self.get_active()?;
+        let inits: Vec<_> = active.iter().map(String::as_ref).collect();
+        let mut authors = self.get_authors(&inits)?;
+        let email = author.email.to_lowercase();
+        match authors
+            .iter()
+            .position(|active| active.email.to_lowercase() == email)
+        {
+            Some(i) => authors.rotate_left(i),
+            None => authors.insert(0, author.clone()),
+        }
+        let mut trailers = trailers(self.attribution()?, &authors);
+        trailers.extend(self.session_trailers()?);
+        Ok(trailers)
+    }
+
+    /// Trailers like `Story: ABC-123` added to every commit by the active
+    /// pair, which only count when set in the same scope as the pair.
+    pub fn session_trailers(&self) -> Result<Vec<String>> {
+        let key = namespaced("trailer");
+        if self.config.scope(&key).ok() != self.active_scope() {
+            return Ok(Vec::new());
+        }
+        self.config.get_multi(&key)
+    }
+
+    /// Adds session trailers, replacing the existing ones unless `keep` is
+    /// set.
+    pub fn set_trailers(&mut self, trailers: &[&str], keep: bool) -> Result<()> {
+        for trailer in trailers {
+            validate_trailer(trailer)?;
+        }
+
+        let key = namespaced("trailer");
+        if !keep {
+            self.config.clear_all(&key)?;
+        }
+        for trailer in trailers {
+            self.config.add(&key, trailer)?;
+        }
+        Ok(())
+    }
+
+    pub fn attribution(&self) -> Result<Attribution> {
+        match self.config.get(&namespaced("attribution")) {
+            Ok(ref mode) if mode == "signoff" => Ok(Attribution::Signoff),
+            Ok(ref mode) if mode == "coauthors" => Ok(Attribution::Coauthors),
+            Ok(mode) => Err(format!("invalid attribution: '{}'", mode).into()),
+            Err(_) => Ok(Attribution::Signoff),
+        }
+    }
+
+    fn get_active(&self) -> Result<Vec<String>> {
+        self.config
+            .get(&self.active_key())
+            .map(|active| active.split('+').map(|s| s.into()).collect())
+    }
+
+    pub fn policy(&self) -> Result<Policy> {
+        match self.config.get(&namespaced("policy")) {
+            Ok(ref policy) if policy == "strict" => Ok(Policy::Strict),
+            Ok(ref policy) if policy == "warn" => Ok(Policy::Warn),
+            Ok(ref policy) if policy == "off" => Ok(Policy::Off),
+            Ok(policy) => Err(format!("invalid policy: '{}'", policy).into()),
+            Err(_) => Ok(Policy::Strict),
+        }
+    }
+
+    /// Whether there's an active pair to attribute a commit to. Without one,
+    /// `git-together.policy` decides whether to refuse the commit, warn about
+    /// it, or let it through.
+    pub fn check_active(&self) -> Result<bool> {
+        if self.get_active().is_ok() {
+            return Ok(true);
+        }
+
+        match self.policy()? {
+            Policy::Strict => Err(format!(
+                "{}; set git-together.policy to warn or off to commit anyway",
+                NO_ACTIVE_PAIR
+            )
+            .into()),
+            Policy::Warn => {
+                eprintln!("warning: {}", NO_ACTIVE_PAIR);
+                Ok(false)
+            }
+            Policy::Off => Ok(false),
+        }
+    }
+
+    pub fn rotation(&self) -> Result<Rotation> {
+        match self.config.get(&namespaced("rotation")) {
+            Ok(ref strategy) if strategy == "none" => Ok(Rotation::None),
+            Ok(ref strategy) if strategy == "round-robin" => Ok(Rotation::RoundRobin),
+            Ok(ref strategy) if strategy == "driver-only" => Ok(Rotation::DriverOnly),
+            Ok(ref strategy) if strategy == "least-recent" => Ok(Rotation::LeastRecent),
+            Ok(strategy) => Err(format!("invalid rotation: '{}'", strategy).into()),
+            Err(_) => Ok(Rotation::RoundRobin),
+        }
+    }
+
+    /// `recent` is the author emails of recent commits, which only the
+    /// least-recent strategy looks at.
+    pub fn rotate_active(&mut self, recent: &[String]) -> Result<()> {
+        let rotation = self.rotation()?;
+        if rotation == Rotation::None {
+            return Ok(());
+        }
+
+        self.get_active().and_then(|active| {
+            let mut inits: Vec<_> = active.iter().map(String::as_ref).collect();
+            let authored: Vec<_> = self
+                .get_authors(&inits)?
+                .iter()
+                .map(|author| {
+                    recent
+                        .iter()
+                        .filter(|email| email.to_lowercase() == author.email.to_lowercase())
+                        .count()
+                })
+                .collect();
+            rotation.rotate(&mut inits, &authored);
+            self.activate(&inits[..], Cause::Rotation, false)
+                .map(|_| ())
+        })
+    }
+
+    /// Which config scope the author for `initials` is defined in
+    pub fn author_scope(&self, initials: &str) -> Result<String> {
+        let mut key = author_field_key(initials, "name");
+        if self.config.scope(&key).is_err() {
+            key = author_key(initials);
+        }
+        let scope = self.config.scope(&key)?;
+
+        // `.git-together` is included from the local config, so only where
+        // the entry came from tells them apart
+        let origin = self.config.origin(&key)?;
+        let filename = format!(".{}", NAMESPACE);
+        if Path::new(&origin).file_name() == Some(filename.as_ref()) {
+            return Ok("file".into());
+        }
+        Ok(scope)
+    }
+
+    /// Which config scope the active authors are set in, if any
+    pub fn active_scope(&self) -> Option<String> {
+        self.config.scope(&self.active_key()).ok()
+    }
+
+    /// The branch the active pair belongs to, if it's not the repo-wide pair
+    pub fn active_branch(&self) -> Option<&str> {
+        self.branch
+            .as_deref()
+            .filter(|&branch| self.config.get(&branch_key(branch)).is_ok())
+    }
+
+    /// Where the active authors are: the current branch's pair in per-branch
+    /// mode, if it has one, or else the repo-wide pair.
+    fn active_key(&self) -> String {
+        match self.active_branch() {
+            Some(branch) => branch_key(branch),
+            None => namespaced("active"),
+        }
+    }
+
+    fn get_authors(&self, inits: &[&str]) -> Result<Vec<Author>> {
+        self.expand_groups(inits)?
+            .iter()
+            .map(|initials| self.get_author(initials))
+            .collect()
+    }
+
+    /// Replaces every `@group` with its members, leaving out anyone who's
+    /// already in the list.
+    pub fn expand_groups(&self, inits: &[&str]) -> Result<Vec<String>> {
+        let mut expanded: Vec<String> = Vec::new();
+        for &initials in inits {
+            let members = match initials.strip_prefix('@') {
+                Some(name) => self.group(name)?,
+                None => vec![initials.into()],
+            };
+            for member in members {
+                if !expanded.contains(&member) {
+                    expanded.push(member);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Every group, by name
+    pub fn groups(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        let prefix = namespaced("groups.");
+        Ok(self
+            .config
+            .get_all(&prefix)?
+            .into_iter()
+            .filter_map(|(key, members)| {
+                let name = key.strip_prefix(&prefix)?.to_string();
+                Some((name, split_group(&members)))
+            })
+            .collect())
+    }
+
+    fn group(&self, name: &str) -> Result<Vec<String>> {
+        let raw = self
+            .config
+            .get(&group_key(name))
+            .chain_err(|| format!("group not found for '@{}'", name))?;
+        let members = split_group(&raw);
+        if members.is_empty() {
+            return Err(format!("group '@{}' has no members", name).into());
+        }
+
+        for member in &members {
+            self.get_author(member)
+                .chain_err(|| format!("invalid member '{}' in group '@{}'", member, name))?;
+        }
+        Ok(members)
+    }
+
+    fn get_author(&self, initials: &str) -> Result<Author> {
+        let fields = self.author_fields(initials)?;
+        if !fields.is_empty() {
+            return self.parse_author_fields(initials, fields);
+        }
+
+        let key = author_key(initials);
+        let raw = self
+            .config
+            .get(&key)
+            .chain_err(|| format!("author not found for '{}'", initials))?;
+        self.parse_author(initials, &raw)
+            .chain_err(|| invalid_config(&self.config, &key))
+    }
+
+    /// The fields of an author in the structured form, if there are any
+    fn author_fields(&self, initials: &str) -> Result<BTreeMap<String, String>> {
+        let prefix = author_field_key(initials, "");
+        Ok(self
+            .config
+            .get_all(&prefix)?
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let field = name.strip_prefix(&prefix)?;
+                if field.contains('.') {
+                    return None;
+                }
+                Some((field.to_lowercase(), value))
+            })
+            .collect())
+    }
+
+    fn parse_author(&self, initials: &str, raw: &str) -> Result<Author> {
+        self.author_parser.parse(raw).chain_err(|| {
+            format!(
+                "invalid author for '{}': '{}'",
+                initials,
+                raw.escape_debug()
+            )
         })
     }
 
-    fn get_active(&self) -> Result<Vec<String>> {
-        self.config
-            .get(&namespaced("active"))
-            .map(|active| active.split('+').map(|s| s.into()).collect())
-    }
+    fn parse_author_fields(
+        &self,
+        initials: &str,
+        fields: BTreeMap<String, String>,
+    ) -> Result<Author> {
+        self.author_parser
+            .parse_fields(fields)
+            .chain_err(|| format!("invalid author for '{}'", initials))
+            .chain_err(|| invalid_config(&self.config, &author_field_key(initials, "name")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::ffi::OsStr;
+    use std::ops::Index;
+
+    use author::{Author, AuthorParser};
+    use config::Config;
+
+    #[test]
+    fn get_authors() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", ""),
+            ("git-together.authors.nn", "Naomi Nagata"),
+            ("git-together.authors.ab", "Amos Burton; aburton"),
+            ("git-together.authors.ak", "Alex Kamal; akamal"),
+            ("git-together.authors.ca", "Chrisjen Avasarala;"),
+            ("git-together.authors.bd", "Bobbie Draper; bdraper@mars.mil"),
+            (
+                "git-together.authors.jm",
+                "Joe Miller; jmiller@starhelix.com",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert!(gt.get_authors(&["jh"]).is_err());
+        assert!(gt.get_authors(&["nn"]).is_err());
+        assert!(gt.get_authors(&["ca"]).is_err());
+        assert!(gt.get_authors(&["jh", "bd"]).is_err());
+
+        assert_eq!(
+            gt.get_authors(&["ab", "ak"]).unwrap(),
+            vec![
+                Author {
+                    name: "Amos Burton".into(),
+                    email: "aburton@rocinante.com".into(),
+                    fields: Default::default(),
+                },
+                Author {
+                    name: "Alex Kamal".into(),
+                    email: "akamal@rocinante.com".into(),
+                    fields: Default::default(),
+                }
+            ]
+        );
+        assert_eq!(
+            gt.get_authors(&["ab", "bd", "jm"]).unwrap(),
+            vec![
+                Author {
+                    name: "Amos Burton".into(),
+                    email: "aburton@rocinante.com".into(),
+                    fields: Default::default(),
+                },
+                Author {
+                    name: "Bobbie Draper".into(),
+                    email: "bdraper@mars.mil".into(),
+                    fields: Default::default(),
+                },
+                Author {
+                    name: "Joe Miller".into(),
+                    email: "jmiller@starhelix.com".into(),
+                    fields: Default::default(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn set_active_solo() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["jh"]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["jh"]);
+        assert_eq!(gt.config["user.name"], "James Holden");
+        assert_eq!(gt.config["user.email"], "jholden@rocinante.com");
+        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+    }
+
+    #[test]
+    fn set_active_pair() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["nn", "jh"]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+        assert_eq!(gt.config["user.name"], "Naomi Nagata");
+        assert_eq!(gt.config["user.email"], "nnagata@rocinante.com");
+        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+    }
+
+    #[test]
+    fn clear_active_pair() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["nn", "jh"]).unwrap();
+        gt.clear_active().unwrap();
+        assert!(gt.get_active().is_err());
+        assert_eq!(gt.config["user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["user.email"], "bdraper@mars.mil");
+        assert!(gt.config.get("git-together.user.name").is_err());
+        assert!(gt.config.get("git-together.user.email").is_err());
+
+        gt.set_active(&["jh"]).unwrap();
+        gt.set_active(&["nn"]).unwrap();
+        gt.clear_active().unwrap();
+        assert_eq!(gt.config["user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["user.email"], "bdraper@mars.mil");
+    }
+
+    #[test]
+    fn clear_active_without_original_user() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["nn", "jh"]).unwrap();
+        gt.set_active(&["jh", "nn"]).unwrap();
+        assert!(gt.config.get("git-together.user.name").is_err());
+
+        gt.clear_active().unwrap();
+        assert!(gt.get_active().is_err());
+        assert!(gt.config.get("user.name").is_err());
+        assert!(gt.config.get("user.email").is_err());
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert!(gt.pause().is_err());
+        assert!(gt.resume().is_err());
+
+        gt.set_active(&["nn", "jh"]).unwrap();
+        gt.config.set("git-together.started", "1595894400").unwrap();
+        gt.pause().unwrap();
+        assert!(gt.is_paused());
+        assert!(gt.pause().is_err());
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+        assert_eq!(gt.config["user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["user.email"], "bdraper@mars.mil");
+
+        gt.resume().unwrap();
+        assert!(!gt.is_paused());
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+        assert_eq!(gt.config["user.name"], "Naomi Nagata");
+        assert_eq!(gt.config["user.email"], "nnagata@rocinante.com");
+        assert_eq!(gt.config["git-together.started"], "1595894400");
+
+        gt.pause().unwrap();
+        gt.set_active(&["jh"]).unwrap();
+        assert!(!gt.is_paused());
+        assert_eq!(gt.config["user.name"], "James Holden");
+
+        gt.pause().unwrap();
+        gt.clear_active().unwrap();
+        assert!(!gt.is_paused());
+        assert!(gt.config.get("git-together.paused").is_err());
+        assert_eq!(gt.config["user.name"], "Bobbie Draper");
+    }
+
+    #[test]
+    fn pause_and_clear_inherited_pair() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+        gt.config
+            .global
+            .insert("git-together.active".into(), "jh+nn".into());
+        gt.config
+            .global
+            .insert("user.name".into(), "James Holden".into());
+
+        let err = gt.pause().unwrap_err();
+        assert!(err.to_string().contains("global config"));
+        assert!(gt.config.get("git-together.paused").is_err());
+        assert!(!gt.is_paused());
+
+        assert!(gt.resume().is_err());
+        assert!(gt.clear_active().is_err());
+        assert_eq!(gt.get_active().unwrap(), vec!["jh", "nn"]);
+        assert!(!gt.config.writable("user.name"));
+    }
+
+    #[test]
+    fn check_session() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.started", "1595894400"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        let later = |hours: i64| time::Timestamp {
+            seconds: 1_595_894_400 + hours * 60 * 60,
+            offset: -7 * 60 * 60,
+        };
+        assert_eq!(gt.started(), Some(1_595_894_400));
+        assert!(gt.check_session(later(100)).is_ok());
+
+        gt.config.set("git-together.expireAfter", "12h").unwrap();
+        assert!(gt.check_session(later(12)).is_ok());
+        assert!(gt.check_session(later(13)).is_err());
+
+        // Started at 17:00 local time
+        gt.config
+            .set("git-together.expireAfter", "end-of-day")
+            .unwrap();
+        assert!(gt.check_session(later(6)).is_ok());
+        assert!(gt.check_session(later(7)).is_err());
+
+        gt.config.set("git-together.expireAfter", "soon").unwrap();
+        assert!(gt.check_session(later(0)).is_err());
+
+        gt.config.set("git-together.expireAfter", "1d").unwrap();
+        gt.config.set("git-together.warnAfter", "often").unwrap();
+        assert!(gt.check_session(later(0)).is_err());
+
+        gt.set_active(&["jh"]).unwrap();
+        assert!(gt.started().unwrap() > 1_595_894_400);
+        gt.clear_active().unwrap();
+        assert_eq!(gt.started(), None);
+        assert!(gt.config.get("git-together.started").is_err());
+    }
+
+    #[test]
+    fn policy() {
+        let config = MockConfig::new(&[("git-together.authors.jh", "James Holden; jholden")]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert_eq!(gt.policy().unwrap(), Policy::Strict);
+        let err = gt.check_active().unwrap_err();
+        assert!(err.to_string().contains("run `git with <initials>` first"));
+        let mut cmd = Command::new("git");
+        let err = gt.signoff(&mut cmd).unwrap_err();
+        assert_eq!(err.to_string(), NO_ACTIVE_PAIR);
+
+        gt.config.set("git-together.policy", "warn").unwrap();
+        assert_eq!(gt.policy().unwrap(), Policy::Warn);
+        assert!(!gt.check_active().unwrap());
+
+        gt.config.set("git-together.policy", "off").unwrap();
+        assert_eq!(gt.policy().unwrap(), Policy::Off);
+        assert!(!gt.check_active().unwrap());
+
+        gt.config.set("git-together.policy", "lax").unwrap();
+        assert!(gt.policy().is_err());
+
+        gt.set_active(&["jh"]).unwrap();
+        assert!(gt.check_active().unwrap());
+    }
+
+    #[test]
+    fn per_branch() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: Some("feature".into()),
+        };
+
+        assert_eq!(gt.get_active().unwrap(), vec!["jh", "nn"]);
+        assert_eq!(gt.active_branch(), None);
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.config["git-together.active"], "nn+jh");
+        assert!(gt.config.get("git-together.branch.feature.active").is_err());
+
+        gt.set_active(&["ca", "jh"]).unwrap();
+        assert_eq!(gt.active_branch(), Some("feature"));
+        assert_eq!(gt.config["git-together.branch.feature.active"], "ca+jh");
+        assert_eq!(gt.config["git-together.active"], "nn+jh");
+        assert!(gt.config.get("git-together.user.name").is_err());
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.config["git-together.branch.feature.active"], "jh+ca");
+        assert_eq!(gt.config["git-together.active"], "nn+jh");
+
+        gt.clear_active().unwrap();
+        assert_eq!(gt.active_branch(), None);
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+        assert_eq!(gt.config["user.name"], "Naomi Nagata");
+    }
+
+    #[test]
+    fn change_active() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+            ("git-together.authors.ak", "Alex Kamal; akamal"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert_eq!(
+            gt.change_active(&["ak"], &[]).unwrap(),
+            vec!["jh", "nn", "ca", "ak"]
+        );
+        assert_eq!(gt.change_active(&[], &["jh"]).unwrap(), vec!["nn", "ca"]);
+        assert_eq!(
+            gt.change_active(&["ak"], &["nn"]).unwrap(),
+            vec!["jh", "ca", "ak"]
+        );
+
+        assert!(gt.change_active(&["jh"], &[]).is_err());
+        assert!(gt.change_active(&["bd"], &[]).is_err());
+        assert!(gt.change_active(&[], &["ak"]).is_err());
+        assert!(gt.change_active(&[], &["jh", "nn", "ca"]).is_err());
+    }
+
+    #[test]
+    fn set_members_keeps_session() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("git-together.authors.ak", "Alex Kamal; akamal"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["jh", "nn"]).unwrap();
+        gt.config.set("git-together.started", "1595894400").unwrap();
+
+        let inits = gt.change_active(&["ak"], &["jh"]).unwrap();
+        let inits: Vec<_> = inits.iter().map(String::as_ref).collect();
+        gt.set_members(&inits).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "ak"]);
+        assert_eq!(gt.config["user.name"], "Naomi Nagata");
+        assert_eq!(gt.started(), Some(1_595_894_400));
+        assert_eq!(gt.history().unwrap().last().unwrap().cause, Cause::Manual);
+
+        gt.set_active(&["jh"]).unwrap();
+        assert_ne!(gt.started(), Some(1_595_894_400));
+    }
+
+    #[test]
+    fn groups() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("git-together.authors.ak", "Alex Kamal; akamal"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+            ("git-together.groups.backend", "jh+nn+ak"),
+            ("git-together.groups.un", "ca+bd"),
+            ("git-together.groups.empty", ""),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert_eq!(
+            gt.expand_groups(&["@backend", "ca", "nn"]).unwrap(),
+            vec!["jh", "nn", "ak", "ca"]
+        );
+        assert_eq!(gt.get_authors(&["ca", "@backend"]).unwrap().len(), 4);
+        assert!(gt.expand_groups(&["@frontend"]).is_err());
+        assert!(gt.expand_groups(&["@un"]).is_err());
+        assert!(gt.expand_groups(&["@empty"]).is_err());
+        assert_eq!(gt.groups().unwrap().len(), 3);
+        assert_eq!(gt.groups().unwrap()["backend"], vec!["jh", "nn", "ak"]);
+
+        gt.set_active(&["ca", "@backend"]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["ca", "jh", "nn", "ak"]);
+        assert_eq!(gt.change_active(&[], &["@backend"]).unwrap(), vec!["ca"]);
+    }
+
+    #[test]
+    fn history() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert!(gt.previous_active().is_err());
+
+        gt.set_active(&["jh", "nn"]).unwrap();
+        gt.set_active(&["jh", "nn"]).unwrap();
+        gt.rotate_active(&[]).unwrap();
+        gt.set_active(&["ca"]).unwrap();
+        gt.clear_active().unwrap();
+
+        let history = gt.history().unwrap();
+        let changes: Vec<_> = history
+            .iter()
+            .map(|change| {
+                (
+                    change.cause,
+                    change.previous.join("+"),
+                    change.active.join("+"),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (Cause::Manual, "".into(), "jh+nn".into()),
+                (Cause::Rotation, "jh+nn".into(), "nn+jh".into()),
+                (Cause::Manual, "nn+jh".into(), "ca".into()),
+                (Cause::Clear, "ca".into(), "".into()),
+            ]
+        );
+        assert_eq!(gt.previous_active().unwrap(), vec!["ca"]);
+
+        let raw = &gt.config.multi["git-together.history"];
+        assert!(raw[0].ends_with(" manual - jh+nn"));
+        assert_eq!(Change::parse(&raw[3]).unwrap(), history[3]);
+        assert!(Change::parse("123 rotation jh").is_err());
+        assert!(Change::parse("now manual - jh").is_err());
+
+        gt.set_active(&["ca"]).unwrap();
+        gt.set_active(&["jh", "nn", "ca"]).unwrap();
+        gt.rotate_active(&[]).unwrap();
+        gt.rotate_active(&[]).unwrap();
+        let last = gt.history().unwrap().pop().unwrap();
+        assert_eq!(last.cause, Cause::Rotation);
+        assert_eq!(last.previous, vec!["nn", "ca", "jh"]);
+        assert_eq!(last.active, vec!["ca", "jh", "nn"]);
+        assert_eq!(gt.history().unwrap().len(), 8);
+        assert_eq!(gt.previous_active().unwrap(), vec!["ca"]);
+
+        for _ in 0..HISTORY_LIMIT {
+            gt.set_active(&["jh", "nn"]).unwrap();
+            gt.rotate_active(&[]).unwrap();
+        }
+        let history = gt.history().unwrap();
+        assert_eq!(history.len(), 2 * HISTORY_LIMIT);
+        assert_eq!(history.last().unwrap().cause, Cause::Rotation);
+
+        // A long session's rotations don't push out the pairs before it
+        gt.set_active(&["ca"]).unwrap();
+        for _ in 0..HISTORY_LIMIT {
+            gt.rotate_active(&[]).unwrap();
+        }
+        gt.set_active(&["jh", "nn"]).unwrap();
+        for _ in 0..HISTORY_LIMIT {
+            gt.rotate_active(&[]).unwrap();
+        }
+        let history = gt.history().unwrap();
+        assert_eq!(history.len(), 2 * HISTORY_LIMIT);
+        assert_eq!(gt.previous_active().unwrap(), vec!["ca"]);
+    }
+
+    #[test]
+    fn multiple_set_active() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            ("user.name", "Bobbie Draper"),
+            ("user.email", "bdraper@mars.mil"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["nn"]).unwrap();
+        gt.set_active(&["jh"]).unwrap();
+        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
+        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+    }
+
+    #[test]
+    fn rotate_active() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+    }
+
+    #[test]
+    fn rotate_active_none() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn"),
+            ("git-together.rotation", "none"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["jh", "nn"]);
+    }
+
+    #[test]
+    fn rotate_active_round_robin() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca"),
+            ("git-together.rotation", "round-robin"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "ca", "jh"]);
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["ca", "jh", "nn"]);
+    }
+
+    #[test]
+    fn rotate_active_driver_only() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca+ak"),
+            ("git-together.rotation", "driver-only"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+            ("git-together.authors.ak", "Alex Kamal; akamal"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["ca", "nn", "ak", "jh"]);
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["ak", "nn", "jh", "ca"]);
+
+        gt.set_active(&["jh", "nn"]).unwrap();
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["jh", "nn"]);
+    }
+
+    #[test]
+    fn rotate_active_least_recent() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca"),
+            ("git-together.rotation", "least-recent"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        let recent: Vec<String> = vec![
+            "jholden@rocinante.com".into(),
+            "nnagata@rocinante.com".into(),
+            "NNagata@Rocinante.com".into(),
+            "avasarala@un.gov".into(),
+        ];
+        gt.rotate_active(&recent).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["ca", "jh", "nn"]);
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["jh", "nn", "ca"]);
+    }
+
+    #[test]
+    fn rotation() {
+        let config = MockConfig::new(&[]);
+        let author_parser = AuthorParser { domain: None };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        assert_eq!(gt.rotation().unwrap(), Rotation::RoundRobin);
+
+        gt.config
+            .set("git-together.rotation", "least-recent")
+            .unwrap();
+        assert_eq!(gt.rotation().unwrap(), Rotation::LeastRecent);
+
+        gt.config.set("git-together.rotation", "bogus").unwrap();
+        assert!(gt.rotation().is_err());
+    }
+
+    #[test]
+    fn signoff_pair() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        let mut cmd = Command::new("git");
+        let cmd = gt.signoff(&mut cmd).unwrap();
+        let envs: HashMap<_, _> = cmd.get_envs().collect();
+        assert_eq!(
+            envs[OsStr::new("GIT_AUTHOR_NAME")],
+            Some(OsStr::new("James Holden"))
+        );
+        assert_eq!(
+            envs[OsStr::new("GIT_COMMITTER_EMAIL")],
+            Some(OsStr::new("nnagata@rocinante.com"))
+        );
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, vec!["--signoff"]);
+    }
+
+    #[test]
+    fn signoff_coauthors() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca"),
+            ("git-together.attribution", "coauthors"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        let mut cmd = Command::new("git");
+        let cmd = gt.signoff(&mut cmd).unwrap();
+        let envs: HashMap<_, _> = cmd.get_envs().collect();
+        assert_eq!(
+            envs[OsStr::new("GIT_AUTHOR_NAME")],
+            Some(OsStr::new("James Holden"))
+        );
+        assert_eq!(
+            envs[OsStr::new("GIT_COMMITTER_EMAIL")],
+            Some(OsStr::new("jholden@rocinante.com"))
+        );
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                "--trailer",
+                "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>",
+                "--trailer",
+                "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>",
+            ]
+        );
+    }
+
+    #[test]
+    fn session_trailers() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["jh", "nn"]).unwrap();
+        gt.set_trailers(&["Story: ABC-123", "Ticket: 42"], false)
+            .unwrap();
+        assert_eq!(
+            gt.session_trailers().unwrap(),
+            vec!["Story: ABC-123", "Ticket: 42"]
+        );
+
+        let mut cmd = Command::new("git");
+        let cmd = gt.signoff(&mut cmd).unwrap();
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                "--signoff",
+                "--trailer",
+                "Story: ABC-123",
+                "--trailer",
+                "Ticket: 42"
+            ]
+        );
+        let jh = gt.get_author("jh").unwrap();
+        assert_eq!(
+            gt.hook_trailers(&jh).unwrap(),
+            vec![
+                "Signed-off-by: Naomi Nagata <nnagata@rocinante.com>",
+                "Story: ABC-123",
+                "Ticket: 42",
+            ]
+        );
+
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.session_trailers().unwrap().len(), 2);
+
+        gt.set_trailers(&["Story: ABC-124"], true).unwrap();
+        assert_eq!(gt.session_trailers().unwrap().len(), 3);
+
+        gt.set_trailers(&["Story: ABC-125"], false).unwrap();
+        assert_eq!(gt.session_trailers().unwrap(), vec!["Story: ABC-125"]);
+
+        assert!(gt.set_trailers(&["Story"], false).is_err());
+        assert!(gt.set_trailers(&["Story:"], false).is_err());
+        assert!(gt.set_trailers(&["User Story: 1"], false).is_err());
+        assert_eq!(gt.session_trailers().unwrap(), vec!["Story: ABC-125"]);
+
+        gt.clear_active().unwrap();
+        assert!(gt.session_trailers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn signoff_coauthors_solo() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh"),
+            ("git-together.attribution", "coauthors"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        let mut cmd = Command::new("git");
+        let cmd = gt.signoff(&mut cmd).unwrap();
+        assert_eq!(cmd.get_args().count(), 0);
+    }
+
+    #[test]
+    fn hook_trailers() {
+        let config = MockConfig::new(&[
+            ("git-together.active", "jh+nn+ca"),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+        let jh = gt.get_author("jh").unwrap();
+
+        assert_eq!(
+            gt.hook_trailers(&jh).unwrap(),
+            vec!["Signed-off-by: Naomi Nagata <nnagata@rocinante.com>"]
+        );
+
+        gt.config
+            .set("git-together.attribution", "coauthors")
+            .unwrap();
+        assert_eq!(
+            gt.hook_trailers(&jh).unwrap(),
+            vec![
+                "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>",
+                "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>",
+            ]
+        );
+
+        let bobbie = Author {
+            name: "Bobbie Draper".into(),
+            email: "bdraper@mars.mil".into(),
+            fields: Default::default(),
+        };
+        assert_eq!(gt.hook_trailers(&bobbie).unwrap().len(), 3);
+
+        gt.config.set("git-together.active", "jh").unwrap();
+        assert!(gt.hook_trailers(&jh).unwrap().is_empty());
+    }
+
+    #[test]
+    fn hook_trailers_amending_after_rotation() {
+        let config = MockConfig::new(&[
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
+            (
+                "git-together.authors.ca",
+                "Chrisjen Avasarala; avasarala@un.gov",
+            ),
+        ]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
+
+        gt.set_active(&["jh", "nn", "ca"]).unwrap();
+        gt.rotate_active(&[]).unwrap();
+        assert_eq!(gt.get_active().unwrap(), vec!["nn", "ca", "jh"]);
+
+        // The amended commit keeps jh as its author
+        let jh = Author {
+            name: "James Holden".into(),
+            email: "JHolden@rocinante.com".into(),
+            fields: Default::default(),
+        };
+        assert_eq!(
+            gt.hook_trailers(&jh).unwrap(),
+            vec!["Signed-off-by: Naomi Nagata <nnagata@rocinante.com>"]
+        );
+
+        gt.config
+            .set("git-together.attribution", "coauthors")
+            .unwrap();
+        assert_eq!(
+            gt.hook_trailers(&jh).unwrap(),
+            vec![
+                "Co-authored-by: Naomi Nagata <nnagata@rocinante.com>",
+                "Co-authored-by: Chrisjen Avasarala <avasarala@un.gov>",
+            ]
+        );
+    }
+
+    #[test]
+    fn attribution() {
+        let config = MockConfig::new(&[]);
+        let author_parser = AuthorParser { domain: None };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
 
-    pub fn rotate_active(&mut self) -> Result<()> {
-        self.get_active().and_then(|active| {
-            let mut inits: Vec<_> = active.iter().map(String::as_ref).collect();
-            if !inits.is_empty() {
-                let author = inits.remove(0);
-                inits.push(author);
-            }
-            self.set_active(&inits[..]).map(|_| ())
-        })
-    }
+        assert_eq!(gt.attribution().unwrap(), Attribution::Signoff);
 
-    fn get_authors(&self, inits: &[&str]) -> Result<Vec<Author>> {
-        inits
-            .iter()
-            .map(|&initials| self.get_author(initials))
-            .collect()
+        gt.config
+            .set("git-together.attribution", "coauthors")
+            .unwrap();
+        assert_eq!(gt.attribution().unwrap(), Attribution::Coauthors);
+
+        gt.config.set("git-together.attribution", "bogus").unwrap();
+        assert!(gt.attribution().is_err());
     }
 
-    fn get_author(&self, initials: &str) -> Result<Author> {
-        self.config
-            .get(&namespaced(&format!("authors.{}", initials)))
-            .chain_err(|| format!("author not found for '{}'", initials))
-            .and_then(|raw| self.parse_author(initials, &raw))
+    #[cfg(unix)]
+    #[test]
+    fn exit_codes() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert_eq!(exit_code(ExitStatus::from_raw(0)), 0);
+        assert_eq!(exit_code(ExitStatus::from_raw(1 << 8)), 1);
+        assert_eq!(exit_code(ExitStatus::from_raw(2)), 130);
+        assert_eq!(exit_code(ExitStatus::from_raw(9)), 137);
     }
 
-    fn parse_author(&self, initials: &str, raw: &str) -> Result<Author> {
-        self.author_parser
-            .parse(raw)
-            .chain_err(|| format!("invalid author for '{}': '{}'", initials, raw))
+    #[test]
+    fn revert_args() {
+        assert!(reverts_one_commit(&["HEAD"]));
+        assert!(reverts_one_commit(&["--no-edit", "-m", "1", "HEAD"]));
+        assert!(!reverts_one_commit(&["HEAD~2..HEAD"]));
+        assert!(!reverts_one_commit(&["HEAD", "HEAD~1"]));
+        assert!(!reverts_one_commit(&["--no-commit", "HEAD"]));
+        assert!(!reverts_one_commit(&["--continue"]));
+
+        assert!(edits_revert_message(&["--edit", "HEAD"]));
+        assert!(!edits_revert_message(&["--edit", "--no-edit", "HEAD"]));
+        assert!(edits_revert_message(&["--no-edit", "-e", "HEAD"]));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn scopes() {
+        let config = MockConfig::new(&[("git-together.authors.jh", "James Holden; jholden")]);
+        let author_parser = AuthorParser {
+            domain: Some("rocinante.com".into()),
+        };
+        let mut gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
+        };
 
-    use std::collections::HashMap;
-    use std::ops::Index;
+        assert_eq!(gt.author_scope("jh").unwrap(), "local");
+        assert!(gt.author_scope("nn").is_err());
+        assert_eq!(gt.active_scope(), None);
 
-    use author::{Author, AuthorParser};
-    use config::Config;
+        gt.config
+            .set("git-together.authors.nn", "Naomi Nagata; nnagata")
+            .unwrap();
+        gt.config.origins.insert(
+            "git-together.authors.nn".into(),
+            "/rocinante/.git-together".into(),
+        );
+        assert_eq!(gt.author_scope("nn").unwrap(), "file");
+
+        gt.set_active(&["jh"]).unwrap();
+        assert_eq!(gt.active_scope(), Some("local".into()));
+    }
 
     #[test]
-    fn get_authors() {
+    fn all_authors() {
         let config = MockConfig::new(&[
-            ("git-together.authors.jh", ""),
-            ("git-together.authors.nn", "Naomi Nagata"),
+            ("git-together.active", "jh+nn"),
             ("git-together.authors.ab", "Amos Burton; aburton"),
-            ("git-together.authors.ak", "Alex Kamal; akamal"),
-            ("git-together.authors.ca", "Chrisjen Avasarala;"),
             ("git-together.authors.bd", "Bobbie Draper; bdraper@mars.mil"),
             (
                 "git-together.authors.jm",
@@ -330,52 +3135,48 @@ mod tests {
         let gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        assert!(gt.get_authors(&["jh"]).is_err());
-        assert!(gt.get_authors(&["nn"]).is_err());
-        assert!(gt.get_authors(&["ca"]).is_err());
-        assert!(gt.get_authors(&["jh", "bd"]).is_err());
-
+        let all_authors = gt.all_authors().unwrap();
+        assert_eq!(all_authors.len(), 3);
         assert_eq!(
-            gt.get_authors(&["ab", "ak"]).unwrap(),
-            vec![
-                Author {
-                    name: "Amos Burton".into(),
-                    email: "aburton@rocinante.com".into(),
-                },
-                Author {
-                    name: "Alex Kamal".into(),
-                    email: "akamal@rocinante.com".into(),
-                }
-            ]
+            all_authors["ab"],
+            Author {
+                name: "Amos Burton".into(),
+                email: "aburton@rocinante.com".into(),
+                fields: Default::default(),
+            }
         );
         assert_eq!(
-            gt.get_authors(&["ab", "bd", "jm"]).unwrap(),
-            vec![
-                Author {
-                    name: "Amos Burton".into(),
-                    email: "aburton@rocinante.com".into(),
-                },
-                Author {
-                    name: "Bobbie Draper".into(),
-                    email: "bdraper@mars.mil".into(),
-                },
-                Author {
-                    name: "Joe Miller".into(),
-                    email: "jmiller@starhelix.com".into(),
-                }
-            ]
+            all_authors["bd"],
+            Author {
+                name: "Bobbie Draper".into(),
+                email: "bdraper@mars.mil".into(),
+                fields: Default::default(),
+            }
+        );
+        assert_eq!(
+            all_authors["jm"],
+            Author {
+                name: "Joe Miller".into(),
+                email: "jmiller@starhelix.com".into(),
+                fields: Default::default(),
+            }
         );
     }
 
     #[test]
-    fn set_active_solo() {
+    fn structured_authors() {
         let config = MockConfig::new(&[
             ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.author.jh.name", "Jim Holden"),
+            ("git-together.author.jh.email", "jim"),
+            ("git-together.author.jh.github", "jholden"),
             ("git-together.authors.nn", "Naomi Nagata; nnagata"),
-            ("user.name", "Bobbie Draper"),
-            ("user.email", "bdraper@mars.mil"),
+            ("git-together.author.ca.name", "Chrisjen Avasarala"),
+            ("git-together.author.ca.email", "avasarala@un.gov"),
+            ("git-together.author.ca.active", "false"),
         ]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
@@ -383,23 +3184,48 @@ mod tests {
         let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        gt.set_active(&["jh"]).unwrap();
-        assert_eq!(gt.get_active().unwrap(), vec!["jh"]);
-        assert_eq!(gt.config["user.name"], "James Holden");
-        assert_eq!(gt.config["user.email"], "jholden@rocinante.com");
-        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
-        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+        let jh = gt.get_author("jh").unwrap();
+        assert_eq!(jh.to_string(), "Jim Holden <jim@rocinante.com>");
+        assert_eq!(jh.field("github"), Some("jholden"));
+
+        let all_authors = gt.all_authors().unwrap();
+        assert_eq!(all_authors.len(), 3);
+        assert_eq!(all_authors["jh"], jh);
+        assert!(!all_authors["ca"].is_active());
+        assert_eq!(gt.author_scope("ca").unwrap(), "local");
+
+        let author = gt.edit_author("jh", "Jim Holden; jholden").unwrap();
+        assert_eq!(author.field("github"), Some("jholden"));
+        assert_eq!(
+            gt.config["git-together.author.jh.email"],
+            "jholden@rocinante.com"
+        );
+        assert_eq!(
+            gt.config["git-together.authors.jh"],
+            "James Holden; jholden"
+        );
+
+        gt.rename_author("ca", "cj", false).unwrap();
+        assert!(gt.get_author("ca").is_err());
+        assert_eq!(gt.config["git-together.author.cj.active"], "false");
+
+        assert!(gt.rename_author("cj", "nn", false).is_err());
+        gt.remove_author("cj").unwrap();
+        assert!(gt.get_author("cj").is_err());
     }
 
     #[test]
-    fn set_active_pair() {
+    fn invalid_authors() {
         let config = MockConfig::new(&[
-            ("git-together.authors.jh", "James Holden; jholden"),
-            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
-            ("user.name", "Bobbie Draper"),
-            ("user.email", "bdraper@mars.mil"),
+            (
+                "git-together.authors.jh",
+                "James Holden; jholden@@rocinante.com",
+            ),
+            ("git-together.author.nn.name", "Naomi\nNagata"),
+            ("git-together.author.nn.email", "nnagata"),
         ]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
@@ -407,122 +3233,190 @@ mod tests {
         let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        gt.set_active(&["nn", "jh"]).unwrap();
-        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
-        assert_eq!(gt.config["user.name"], "Naomi Nagata");
-        assert_eq!(gt.config["user.email"], "nnagata@rocinante.com");
-        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
-        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+        let error = gt.get_author("jh").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid git-together.authors.jh in local config"
+        );
+        assert!(error
+            .iter()
+            .any(|cause| cause.to_string().contains("more than one '@'")));
+
+        let error = gt.get_author("nn").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid git-together.author.nn.name in local config"
+        );
+        assert!(gt.all_authors().is_err());
+
+        assert!(gt
+            .set_trailers(&["Story: ABC-123\nSigned-off-by: Mallory"], false)
+            .is_err());
+        assert!(gt.set_trailers(&["Story: ABC\t123"], false).is_err());
     }
 
     #[test]
-    fn clear_active_pair() {
-        let config = MockConfig::new(&[
-            ("git-together.authors.jh", "James Holden; jholden"),
-            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
-            ("user.name", "Bobbie Draper"),
-            ("user.email", "bdraper@mars.mil"),
-        ]);
+    fn add_author() {
+        let config = MockConfig::new(&[("git-together.authors.jh", "James Holden; jholden")]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
         };
         let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        gt.set_active(&["nn", "jh"]).unwrap();
-        gt.clear_active().unwrap();
-        assert!(gt.get_active().is_err());
-        assert!(gt.config.get("user.name").is_err());
-        assert!(gt.config.get("user.email").is_err());
+        let author = gt.add_author("nn", "Naomi Nagata; nnagata", false).unwrap();
+        assert_eq!(author.email, "nnagata@rocinante.com");
+        assert_eq!(
+            gt.config["git-together.authors.nn"],
+            "Naomi Nagata; nnagata"
+        );
+
+        assert!(gt.add_author("ca", "Chrisjen Avasarala", false).is_err());
+        assert!(gt
+            .add_author("c.a", "Chrisjen Avasarala; avasarala", false)
+            .is_err());
+        assert!(gt
+            .add_author("jh+nn", "Naomi Nagata; nnagata", false)
+            .is_err());
+
+        assert!(gt.add_author("jh", "Julie Mao; jmao", false).is_err());
+        assert_eq!(
+            gt.config["git-together.authors.jh"],
+            "James Holden; jholden"
+        );
+
+        gt.add_author("jh", "Julie Mao; jmao", true).unwrap();
+        assert_eq!(gt.config["git-together.authors.jh"], "Julie Mao; jmao");
+
+        gt.config.global.insert(
+            "git-together.authors.ca".into(),
+            "Chrisjen Avasarala; avasarala@un.gov".into(),
+        );
+        assert!(gt
+            .add_author("ca", "Chrisjen Avasarala; cja", false)
+            .is_err());
+        gt.add_author("ca", "Chrisjen Avasarala; cja", true)
+            .unwrap();
+        assert_eq!(
+            gt.config["git-together.authors.ca"],
+            "Chrisjen Avasarala; cja"
+        );
+
+        gt.config.included.insert(
+            "git-together.authors.nn".into(),
+            "Naomi Nagata; nnagata".into(),
+        );
+        gt.config.origins.insert(
+            "git-together.authors.nn".into(),
+            "/rocinante/.git-together".into(),
+        );
+        let err = gt
+            .add_author("nn", "Naomi Nagata; naomi", true)
+            .unwrap_err();
+        assert!(err.to_string().contains("/rocinante/.git-together"));
+        assert_eq!(gt.get_author("nn").unwrap().email, "nnagata@rocinante.com");
     }
 
     #[test]
-    fn multiple_set_active() {
-        let config = MockConfig::new(&[
-            ("git-together.authors.jh", "James Holden; jholden"),
-            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
-            ("user.name", "Bobbie Draper"),
-            ("user.email", "bdraper@mars.mil"),
-        ]);
+    fn edit_author() {
+        let config = MockConfig::new(&[("git-together.authors.jh", "James Holden; jholden")]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
         };
         let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        gt.set_active(&["nn"]).unwrap();
-        gt.set_active(&["jh"]).unwrap();
-        assert_eq!(gt.config["git-together.user.name"], "Bobbie Draper");
-        assert_eq!(gt.config["git-together.user.email"], "bdraper@mars.mil");
+        assert!(gt.edit_author("nn", "Naomi Nagata; nnagata").is_err());
+        assert!(gt.edit_author("jh", "James Holden").is_err());
+
+        let author = gt.edit_author("jh", "Jim Holden; jholden").unwrap();
+        assert_eq!(author.name, "Jim Holden");
+        assert_eq!(gt.config["git-together.authors.jh"], "Jim Holden; jholden");
+
+        gt.config.included.insert(
+            "git-together.authors.nn".into(),
+            "Naomi Nagata; nnagata".into(),
+        );
+        assert!(gt.edit_author("nn", "Naomi Nagata; naomi").is_err());
     }
 
     #[test]
-    fn rotate_active() {
-        let config = MockConfig::new(&[
-            ("git-together.active", "jh+nn"),
-            ("git-together.authors.jh", "James Holden; jholden"),
-            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
-        ]);
+    fn remove_author() {
+        let config = MockConfig::new(&[("git-together.authors.jh", "James Holden; jholden")]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
         };
         let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        gt.rotate_active().unwrap();
-        assert_eq!(gt.get_active().unwrap(), vec!["nn", "jh"]);
+        assert!(gt.remove_author("nn").is_err());
+
+        gt.remove_author("jh").unwrap();
+        assert!(gt.config.get("git-together.authors.jh").is_err());
+
+        gt.config.global.insert(
+            "git-together.authors.nn".into(),
+            "Naomi Nagata; nnagata".into(),
+        );
+        let err = gt.remove_author("nn").unwrap_err();
+        assert!(err.to_string().contains("global config"));
+        assert!(gt.get_author("nn").is_ok());
     }
 
     #[test]
-    fn all_authors() {
+    fn rename_author() {
         let config = MockConfig::new(&[
-            ("git-together.active", "jh+nn"),
-            ("git-together.authors.ab", "Amos Burton; aburton"),
-            ("git-together.authors.bd", "Bobbie Draper; bdraper@mars.mil"),
-            (
-                "git-together.authors.jm",
-                "Joe Miller; jmiller@starhelix.com",
-            ),
+            ("git-together.authors.jh", "James Holden; jholden"),
+            ("git-together.authors.nn", "Naomi Nagata; nnagata"),
         ]);
         let author_parser = AuthorParser {
             domain: Some("rocinante.com".into()),
         };
-        let gt = GitTogether {
+        let mut gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        let all_authors = gt.all_authors().unwrap();
-        assert_eq!(all_authors.len(), 3);
+        assert!(gt.rename_author("ca", "cja", false).is_err());
+        assert!(gt.rename_author("jh", "nn", false).is_err());
         assert_eq!(
-            all_authors["ab"],
-            Author {
-                name: "Amos Burton".into(),
-                email: "aburton@rocinante.com".into(),
-            }
+            gt.config["git-together.authors.jh"],
+            "James Holden; jholden"
         );
+
+        gt.rename_author("jh", "jho", false).unwrap();
+        assert!(gt.config.get("git-together.authors.jh").is_err());
         assert_eq!(
-            all_authors["bd"],
-            Author {
-                name: "Bobbie Draper".into(),
-                email: "bdraper@mars.mil".into(),
-            }
+            gt.config["git-together.authors.jho"],
+            "James Holden; jholden"
         );
+
+        gt.rename_author("jho", "nn", true).unwrap();
         assert_eq!(
-            all_authors["jm"],
-            Author {
-                name: "Joe Miller".into(),
-                email: "jmiller@starhelix.com".into(),
-            }
+            gt.config["git-together.authors.nn"],
+            "James Holden; jholden"
         );
+
+        gt.config.global.insert(
+            "git-together.authors.ca".into(),
+            "Chrisjen Avasarala; avasarala@un.gov".into(),
+        );
+        assert!(gt.rename_author("ca", "cja", false).is_err());
+        assert!(gt.config.get("git-together.authors.cja").is_err());
+        assert!(gt.get_author("ca").is_ok());
     }
 
     #[test]
@@ -534,12 +3428,13 @@ mod tests {
         let gt = GitTogether {
             config,
             author_parser,
+            branch: None,
         };
 
-        assert_eq!(gt.is_signoff_cmd("commit"), true);
-        assert_eq!(gt.is_signoff_cmd("merge"), true);
-        assert_eq!(gt.is_signoff_cmd("revert"), true);
-        assert_eq!(gt.is_signoff_cmd("bisect"), false);
+        assert!(gt.is_signoff_cmd("commit"));
+        assert!(gt.is_signoff_cmd("merge"));
+        assert!(gt.is_signoff_cmd("revert"));
+        assert!(!gt.is_signoff_cmd("bisect"));
     }
 
     #[test]
@@ -551,21 +3446,58 @@ mod tests {
         let gt = GitTogether {
             config,
             author_parser,
+            branch: None,
+        };
+
+        assert!(gt.is_signoff_cmd("ci"));
+        assert!(gt.is_signoff_cmd("m"));
+        assert!(gt.is_signoff_cmd("rv"));
+    }
+
+    #[test]
+    fn expand_alias() {
+        let config = MockConfig::new(&[
+            ("alias.ci", "commit -v"),
+            ("alias.rv", "revert"),
+            ("alias.rvn", "rv --no-edit"),
+            ("alias.commit", "log"),
+            ("alias.up", "!git pull --rebase"),
+            ("alias.loop", "loop"),
+        ]);
+        let author_parser = AuthorParser { domain: None };
+        let gt = GitTogether {
+            config,
+            author_parser,
+            branch: None,
         };
 
-        assert_eq!(gt.is_signoff_cmd("ci"), true);
-        assert_eq!(gt.is_signoff_cmd("m"), true);
-        assert_eq!(gt.is_signoff_cmd("rv"), true);
+        assert_eq!(gt.expand_alias("commit"), vec!["commit"]);
+        assert_eq!(gt.expand_alias("ci"), vec!["commit", "-v"]);
+        assert_eq!(gt.expand_alias("rv"), vec!["revert"]);
+        assert_eq!(gt.expand_alias("rvn"), vec!["revert", "--no-edit"]);
+        assert_eq!(gt.expand_alias("m"), vec!["m"]);
+        assert!(gt.expand_alias("up").is_empty());
+        assert!(gt.expand_alias("loop").is_empty());
     }
 
     struct MockConfig {
         data: HashMap<String, String>,
+        multi: HashMap<String, Vec<String>>,
+        origins: HashMap<String, String>,
+        /// Values included into the local config, which can't be changed
+        included: HashMap<String, String>,
+        /// Values from the global config, which can't be changed
+        global: HashMap<String, String>,
     }
 
     impl MockConfig {
         fn new(data: &[(&str, &str)]) -> MockConfig {
             MockConfig {
                 data: data.iter().map(|&(k, v)| (k.into(), v.into())).collect(),
+                multi: HashMap::new(),
+                origins: HashMap::new(),
+                included: HashMap::new(),
+                global: HashMap::new(),
             }
         }
     }
@@ -581,31 +3513,80 @@ mod tests {
     impl Config for MockConfig {
         fn get(&self, name: &str) -> Result<String> {
             self.data
-                .get(name.into())
+                .get(name)
+                .or_else(|| self.included.get(name))
+                .or_else(|| self.global.get(name))
                 .cloned()
                 .ok_or(format!("name not found: '{}'", name).into())
         }
 
+        fn scope(&self, name: &str) -> Result<String> {
+            if self.writable(name) || self.included.contains_key(name) {
+                return Ok("local".into());
+            }
+            self.get(name).map(|_| "global".into())
+        }
+
+        fn origin(&self, name: &str) -> Result<String> {
+            let scope = self.scope(name)?;
+            Ok(self
+                .origins
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| format!("{} config", scope)))
+        }
+
+        fn writable(&self, name: &str) -> bool {
+            self.data.contains_key(name) || self.multi.contains_key(name)
+        }
+
+        fn shadowable(&self, name: &str) -> bool {
+            !self.included.contains_key(name)
+        }
+
         fn get_all(&self, glob: &str) -> Result<HashMap<String, String>> {
             Ok(self
-                .data
+                .global
                 .iter()
+                .chain(&self.included)
+                .chain(&self.data)
                 .filter(|&(name, _)| name.contains(glob))
                 .map(|(name, value)| (name.clone(), value.clone()))
                 .collect())
         }
 
-        fn add(&mut self, _: &str, _: &str) -> Result<()> {
-            unimplemented!();
+        fn get_multi(&self, name: &str) -> Result<Vec<String>> {
+            Ok(self.multi.get(name).cloned().unwrap_or_default())
+        }
+
+        fn add(&mut self, name: &str, value: &str) -> Result<()> {
+            self.multi
+                .entry(name.into())
+                .or_default()
+                .push(value.into());
+            Ok(())
         }
 
         fn set(&mut self, name: &str, value: &str) -> Result<()> {
+            if self.included.contains_key(name) {
+                return Err("entry is not unique due to being included".into());
+            }
             self.data.insert(name.into(), value.into());
             Ok(())
         }
 
         fn clear(&mut self, name: &str) -> Result<()> {
-            self.data.remove(name.into());
+            if self.included.contains_key(name) {
+                return Err("entry is not unique due to being included".into());
+            }
+            if self.data.remove(name).is_none() && self.global.contains_key(name) {
+                return Err(format!("could not find key '{}' to delete", name).into());
+            }
+            Ok(())
+        }
+
+        fn clear_all(&mut self, name: &str) -> Result<()> {
+            self.multi.remove(name);
             Ok(())
         }
     }
@@ -0,0 +1,148 @@
+use crate::errors::*;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// A point in time along with the local UTC offset, both in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub offset: i64,
+}
+
+impl Timestamp {
+    pub fn now() -> Result<Self> {
+        // libgit2 already knows how to find the local offset on every platform
+        let when = git2::Signature::now("git-together", "git-together")
+            .chain_err(|| "error getting the current time")?
+            .when();
+        Ok(Timestamp {
+            seconds: when.seconds(),
+            offset: i64::from(when.offset_minutes()) * MINUTE,
+        })
+    }
+
+    /// Days since the epoch in local time
+    pub fn local_days(&self) -> i64 {
+        (self.seconds + self.offset).div_euclid(DAY)
+    }
+}
+
+/// Parses durations like `30m`, `12h`, `2d` or `1w` into seconds.
+pub fn parse_duration(raw: &str) -> Result<i64> {
+    let raw = raw.trim();
+    let unit = match raw.chars().last() {
+        Some('m') => MINUTE,
+        Some('h') => HOUR,
+        Some('d') => DAY,
+        Some('w') => WEEK,
+        _ => {
+            return Err(format!("invalid duration: '{}'", raw).into());
+        }
+    };
+
+    raw[..raw.len() - 1]
+        .parse::<i64>()
+        .ok()
+        .filter(|&n| n >= 0)
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| format!("invalid duration: '{}'", raw).into())
+}
+
+/// Formats a number of seconds roughly, like `3d`, `14h` or `25m`.
+pub fn format_duration(seconds: i64) -> String {
+    if seconds >= 2 * DAY {
+        format!("{}d", seconds / DAY)
+    } else if seconds >= HOUR {
+        format!("{}h", seconds / HOUR)
+    } else {
+        format!("{}m", seconds / MINUTE)
+    }
+}
+
+/// Parses either a duration before `now` (`2w`) or a local date
+/// (`2020-07-28`) into seconds since the epoch.
+pub fn parse_since(raw: &str, now: Timestamp) -> Result<i64> {
+    if let Ok(duration) = parse_duration(raw) {
+        return Ok(now.seconds - duration);
+    }
+
+    let parts: Vec<_> = raw.trim().split('-').map(str::parse::<i64>).collect();
+    match parts.as_slice() {
+        [Ok(year), Ok(month), Ok(day)] if (1..=12).contains(month) && (1..=31).contains(day) => {
+            Ok(days_from_civil(*year, *month, *day) * DAY - now.offset)
+        }
+        _ => Err(format!("invalid date: '{}'", raw).into()),
+    }
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("30m").unwrap(), 1800);
+        assert_eq!(parse_duration("12h").unwrap(), 43_200);
+        assert_eq!(parse_duration("2d").unwrap(), 172_800);
+        assert_eq!(parse_duration("1w").unwrap(), 604_800);
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("12").is_err());
+        assert!(parse_duration("-1h").is_err());
+        assert!(parse_duration("twelveh").is_err());
+        assert!(parse_duration("9223372036854775807w").is_err());
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(1500), "25m");
+        assert_eq!(format_duration(50_400), "14h");
+        assert_eq!(format_duration(172_799), "47h");
+        assert_eq!(format_duration(259_200), "3d");
+    }
+
+    #[test]
+    fn since() {
+        let now = Timestamp {
+            seconds: 1_595_894_400,
+            offset: -7 * HOUR,
+        };
+
+        assert_eq!(parse_since("1d", now).unwrap(), 1_595_808_000);
+        assert_eq!(parse_since("2020-07-28", now).unwrap(), 1_595_919_600);
+        assert_eq!(parse_since("1970-01-01", now).unwrap(), 25_200);
+
+        assert!(parse_since("2020-13-01", now).is_err());
+        assert!(parse_since("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn local_days() {
+        let now = Timestamp {
+            seconds: 1_595_894_400,
+            offset: 0,
+        };
+        assert_eq!(now.local_days(), 18_471);
+
+        let now = Timestamp {
+            seconds: 1_595_894_400,
+            offset: -HOUR,
+        };
+        assert_eq!(now.local_days(), 18_470);
+    }
+}